@@ -0,0 +1,175 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::crane::Crane;
+use crate::{Crates, Move, MoveError};
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+const HIGHLIGHT: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+
+/// How frames are written: redrawn in place with ANSI highlighting, or appended as plain text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Clears the terminal before each frame and shows the lifted crates in reverse video.
+    Terminal,
+    /// Separates frames with an empty line and draws the lifted crates as `{X}` instead of `[X]`.
+    Headless,
+}
+
+#[derive(Debug)]
+pub enum AnimationError {
+    Io(io::Error),
+    Move(MoveError),
+}
+
+impl From<io::Error> for AnimationError {
+    fn from(err: io::Error) -> Self {
+        AnimationError::Io(err)
+    }
+}
+
+impl From<MoveError> for AnimationError {
+    fn from(err: MoveError) -> Self {
+        AnimationError::Move(err)
+    }
+}
+
+impl fmt::Display for AnimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationError::Io(err) => write!(f, "failed to write a frame: {err}"),
+            AnimationError::Move(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for AnimationError {}
+
+/// Replays a move list frame by frame.
+///
+/// Each frame shows the state before a move with the crates about to be lifted highlighted; the last one shows
+/// the final state and its top crates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Animation {
+    pub style: Style,
+    pub delay: Duration,
+}
+
+impl Animation {
+    /// Runs `crane` over `moves` through [`Crates::apply`], writing a frame before every move, and returns the
+    /// final state.
+    pub fn run<C: Crane>(
+        &self,
+        crates: &Crates,
+        moves: &[Move],
+        crane: &mut C,
+        out: &mut impl Write,
+    ) -> Result<Crates, AnimationError> {
+        let mut crates = crates.clone();
+        for (move_idx, &mv) in moves.iter().enumerate() {
+            let header = format!("move {}/{}: {mv}", move_idx + 1, moves.len());
+            self.write_frame(out, &header, &render_frame(&crates, Some(mv), self.style))?;
+
+            crates.apply(mv, crane).map_err(|underflow| MoveError { move_idx, mv, underflow })?;
+        }
+
+        let tops = crates.get_top_crates().into_iter().map(|top| top.unwrap_or_else(|| "-".to_string()));
+        let header = format!("done: {}", tops.collect::<Vec<_>>().join(" "));
+        self.write_frame(out, &header, &render_frame(&crates, None, self.style))?;
+        Ok(crates)
+    }
+
+    fn write_frame(&self, out: &mut impl Write, header: &str, frame: &str) -> io::Result<()> {
+        match self.style {
+            Style::Terminal => write!(out, "{CLEAR_SCREEN}{header}\n\n{frame}\n")?,
+            Style::Headless => write!(out, "{header}\n{frame}\n\n")?,
+        }
+        out.flush()?;
+
+        if !self.delay.is_zero() {
+            thread::sleep(self.delay);
+        }
+        Ok(())
+    }
+}
+
+/// Draws `crates`, highlighting the top `lifted.num` crates of stack `lifted.from`.
+pub fn render_frame(crates: &Crates, lifted: Option<Move>, style: Style) -> String {
+    let is_lifted = |stack_idx: usize, level: usize| match lifted {
+        Some(mv) => stack_idx + 1 == mv.from && level + mv.num >= crates.state[stack_idx].len(),
+        None => false,
+    };
+
+    let mut frame = String::new();
+    crates
+        .draw(&mut frame, |stack_idx, level, label| match (is_lifted(stack_idx, level), style) {
+            (false, _) => format!("[{label}]"),
+            (true, Style::Terminal) => format!("{HIGHLIGHT}[{label}]{RESET}"),
+            (true, Style::Headless) => format!("{{{label}}}"),
+        })
+        .unwrap();
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::parse::read_input;
+    use crate::{p1, p2};
+
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    fn headless() -> Animation {
+        Animation { style: Style::Headless, delay: Duration::ZERO }
+    }
+
+    #[test]
+    fn test_render_frame() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        assert_eq!(render_frame(&crates, None, Style::Headless), crates.to_string());
+        assert_eq!(render_frame(&crates, Some(moves[1]), Style::Headless), "    [D]\n{N} [C]\n{Z} [M] [P]\n 1   2   3");
+
+        let mv = Move { num: 2, from: 2, to: 1 };
+        assert_eq!(render_frame(&crates, Some(mv), Style::Headless), "    {D}\n[N] {C}\n[Z] [M] [P]\n 1   2   3");
+        assert_eq!(
+            render_frame(&crates, Some(mv), Style::Terminal),
+            "    \x1b[7m[D]\x1b[0m\n[N] \x1b[7m[C]\x1b[0m\n[Z] [M] [P]\n 1   2   3"
+        );
+    }
+
+    #[test]
+    fn test_headless_frames() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        let mut out = vec![];
+        let end = headless().run(&crates, &moves, &mut CrateMover9000, &mut out).unwrap();
+        assert_eq!(end.get_top_crates().into_iter().flatten().collect::<String>(), p1(&crates, &moves).unwrap());
+
+        let frames = String::from_utf8(out).unwrap();
+        let frames = frames.trim_end().split("\n\n").collect::<Vec<_>>();
+        assert_eq!(frames.len(), moves.len() + 1);
+        assert_eq!(frames[0], "move 1/4: move 1 from 2 to 1\n    {D}\n[N] [C]\n[Z] [M] [P]\n 1   2   3");
+        assert_eq!(frames[4], "done: C M Z\n        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3");
+    }
+
+    #[test]
+    fn test_run_matches_p2() {
+        let (crates, moves) = read_input("../inputs/d05").unwrap();
+        let end = headless().run(&crates, &moves, &mut CrateMover9001, &mut io::sink()).unwrap();
+        assert_eq!(end.get_top_crates().into_iter().flatten().collect::<String>(), p2(&crates, &moves).unwrap());
+    }
+
+    #[test]
+    fn test_run_stops_at_illegal_move() {
+        let (crates, mut moves) = read_input(TEST_PATH).unwrap();
+        moves[2].num = 10;
+
+        let mut out = vec![];
+        let err = headless().run(&crates, &moves, &mut CrateMover9001, &mut out).unwrap_err();
+        assert!(matches!(err, AnimationError::Move(MoveError { move_idx: 2, .. })));
+        assert_eq!(String::from_utf8(out).unwrap().matches("move ").count(), 3 * 2);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::crane::{Crane, CrateMover9000, CrateMover9001, LimitedCrane};
+use crate::{Crates, Move};
+
+/// A crane whose moves can be traced backwards.
+///
+/// Only stateless cranes qualify: the landing order of a move must depend on nothing but the move itself.
+pub trait Traceable: Crane {
+    /// For a crate that lands at `depth` (0 = top) on the destination stack, returns the depth it was lifted from
+    /// on the source stack. `depth` is always less than `num`.
+    fn lifted_from(&self, num: usize, depth: usize) -> usize;
+
+    /// Same as [`lifted_from`](Traceable::lifted_from), for a move whose source and destination are the same stack.
+    fn lifted_from_same_stack(&self, num: usize, depth: usize) -> usize {
+        self.lifted_from(num, depth)
+    }
+}
+
+impl Traceable for CrateMover9000 {
+    fn lifted_from(&self, num: usize, depth: usize) -> usize {
+        num - 1 - depth
+    }
+
+    fn lifted_from_same_stack(&self, _num: usize, depth: usize) -> usize {
+        // Each crate goes straight back where it was, so nothing moves.
+        depth
+    }
+}
+
+impl Traceable for CrateMover9001 {
+    fn lifted_from(&self, _num: usize, depth: usize) -> usize {
+        depth
+    }
+}
+
+impl Traceable for LimitedCrane {
+    fn lifted_from(&self, num: usize, depth: usize) -> usize {
+        // Trips take `max_lift` crates off the top each, and each lands on top of the previous one, so the last
+        // (possibly short) trip ends up on top.
+        let max_lift = self.max_lift.get();
+        let trips = num.div_ceil(max_lift);
+        let last_trip = num - (trips - 1) * max_lift;
+        if depth < last_trip {
+            (trips - 1) * max_lift + depth
+        } else {
+            let depth = depth - last_trip;
+            (trips - 2 - depth / max_lift) * max_lift + depth % max_lift
+        }
+    }
+
+    fn lifted_from_same_stack(&self, _num: usize, depth: usize) -> usize {
+        // Every trip picks up the crates the previous trip just put down, so nothing moves.
+        depth
+    }
+}
+
+/// Maps a `(stack index, depth)` position after `mv` to where that crate was before it.
+///
+/// Depths count from the top, which lets positions below the bottom of a stack stand in for "no crate here":
+/// they move consistently with the real crates as long as every move is legal.
+fn trace_back<C: Traceable>(crane: &C, mv: Move, (stack, depth): (usize, usize)) -> (usize, usize) {
+    let (from, to) = (mv.from - 1, mv.to - 1);
+    if stack != to {
+        return if stack == from { (stack, depth + mv.num) } else { (stack, depth) };
+    }
+
+    match (depth < mv.num, from == to) {
+        (true, true) => (stack, crane.lifted_from_same_stack(mv.num, depth)),
+        (true, false) => (from, crane.lifted_from(mv.num, depth)),
+        (false, true) => (stack, depth),
+        (false, false) => (stack, depth - mv.num),
+    }
+}
+
+/// Computes the same answer as [`simulate`](crate::simulate) without moving any crates: it follows only the
+/// positions that end up on top of each stack back through the move list, then reads their labels off the
+/// initial state. This takes `O(moves * stacks)` time regardless of how tall the stacks are.
+///
+/// Assumes every move is legal, as [`Crates::apply_all`] would check.
+pub fn top_crates_backward<C: Traceable>(crates: &Crates, moves: &[Move], crane: &C) -> Vec<Option<String>> {
+    let mut positions = (0..crates.state.len()).map(|stack| (stack, 0)).collect::<Vec<_>>();
+    for &mv in moves.iter().rev() {
+        for position in positions.iter_mut() {
+            *position = trace_back(crane, mv, *position);
+        }
+    }
+
+    positions
+        .into_iter()
+        .map(|(stack, depth)| {
+            let stack = &crates.state[stack];
+            stack.len().checked_sub(depth + 1).map(|height| stack[height].clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    use crate::generate::{generate, PuzzleSpec, Rng};
+    use crate::parse::read_input;
+    use crate::simulate;
+
+    #[test]
+    fn test_matches_puzzle_answers() {
+        for path in ["../inputs/d05_test", "../inputs/d05"] {
+            let (crates, moves) = read_input(path).unwrap();
+            assert_eq!(
+                top_crates_backward(&crates, &moves, &CrateMover9000),
+                simulate(&crates, &moves, &mut CrateMover9000).unwrap()
+            );
+            assert_eq!(
+                top_crates_backward(&crates, &moves, &CrateMover9001),
+                simulate(&crates, &moves, &mut CrateMover9001).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_lifted_from_limited_crane() {
+        let crane = LimitedCrane { max_lift: NonZeroUsize::new(2).unwrap() };
+        // Source ABCDE (E on top) lands as DEBCA, i.e. top-down A, C, B, E, D.
+        let lifted_depths = (0..5).map(|depth| crane.lifted_from(5, depth)).collect::<Vec<_>>();
+        assert_eq!(lifted_depths, vec![4, 2, 3, 0, 1]);
+    }
+
+    #[test]
+    fn test_matches_forward_simulation_on_random_inputs() {
+        let mut rng = Rng::new(0x5eed);
+        for _ in 0..200 {
+            let spec = PuzzleSpec {
+                num_stacks: 1 + rng.below(9),
+                max_height: 30,
+                num_moves: rng.below(200),
+                max_move: 30,
+                max_label_len: 2,
+            };
+            let (crates, moves) = generate(spec, &mut rng);
+
+            assert_eq!(
+                top_crates_backward(&crates, &moves, &CrateMover9000),
+                simulate(&crates, &moves, &mut CrateMover9000).unwrap()
+            );
+            assert_eq!(
+                top_crates_backward(&crates, &moves, &CrateMover9001),
+                simulate(&crates, &moves, &mut CrateMover9001).unwrap()
+            );
+
+            let crane = LimitedCrane { max_lift: NonZeroUsize::new(1 + rng.below(4)).unwrap() };
+            assert_eq!(
+                top_crates_backward(&crates, &moves, &crane),
+                simulate(&crates, &moves, &mut crane.clone()).unwrap()
+            );
+        }
+    }
+}
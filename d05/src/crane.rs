@@ -0,0 +1,178 @@
+use std::num::NonZeroUsize;
+
+use crate::Move;
+
+/// A crane model: the rule for how the crates of one [`Move`] land on the destination stack.
+pub trait Crane {
+    /// Moves the top `mv.num` crates of stack `mv.from` onto stack `mv.to`.
+    ///
+    /// Callers guarantee that both stacks exist and that the source holds at least `mv.num` crates.
+    /// The crane only rearranges crates, so it works the same for labels and for any other per-crate value.
+    fn execute<T>(&mut self, mv: Move, stacks: &mut [Vec<T>]);
+
+    /// How many trips the crane makes to move `num` crates.
+    fn lifts(&self, num: usize) -> usize;
+}
+
+/// Takes the top `num` crates off `stack`, bottom-most first.
+fn lift<T>(stacks: &mut [Vec<T>], stack: usize, num: usize) -> Vec<T> {
+    let source = &mut stacks[stack - 1];
+    source.split_off(source.len() - num)
+}
+
+/// Lifts one crate at a time, so the moved crates land in reverse order.
+///
+/// A move onto the same stack is a no-op, since each crate is put straight back where it was picked up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn execute<T>(&mut self, mv: Move, stacks: &mut [Vec<T>]) {
+        if mv.from == mv.to {
+            return;
+        }
+        let mut elems = lift(stacks, mv.from, mv.num);
+        elems.reverse();
+        stacks[mv.to - 1].append(&mut elems);
+    }
+
+    fn lifts(&self, num: usize) -> usize {
+        num
+    }
+}
+
+/// Lifts the whole slice at once, keeping its order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn execute<T>(&mut self, mv: Move, stacks: &mut [Vec<T>]) {
+        let mut elems = lift(stacks, mv.from, mv.num);
+        stacks[mv.to - 1].append(&mut elems);
+    }
+
+    fn lifts(&self, _num: usize) -> usize {
+        1
+    }
+}
+
+/// Lifts at most `max_lift` crates per trip, keeping each trip's order.
+///
+/// `max_lift == 1` behaves like [`CrateMover9000`], and a `max_lift` at least as large as any move behaves like
+/// [`CrateMover9001`]. A move onto the same stack is a no-op, since each trip picks up the crates the previous one put
+/// down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LimitedCrane {
+    pub max_lift: NonZeroUsize,
+}
+
+impl Crane for LimitedCrane {
+    fn execute<T>(&mut self, mv: Move, stacks: &mut [Vec<T>]) {
+        let mut remaining = mv.num;
+        while remaining > 0 {
+            let batch = remaining.min(self.max_lift.get());
+            let mut elems = lift(stacks, mv.from, batch);
+            stacks[mv.to - 1].append(&mut elems);
+            remaining -= batch;
+        }
+    }
+
+    fn lifts(&self, num: usize) -> usize {
+        num.div_ceil(self.max_lift.get())
+    }
+}
+
+/// Lifts each move's slice at once like [`CrateMover9001`], but flips every other batch it delivers,
+/// starting with the second one.
+///
+/// Only the parity of the batch count is kept, so cranes that will flip the same batches compare equal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AlternatingCrane {
+    /// Whether the next batch lands reversed.
+    pub flip_next: bool,
+}
+
+impl Crane for AlternatingCrane {
+    fn execute<T>(&mut self, mv: Move, stacks: &mut [Vec<T>]) {
+        let mut elems = lift(stacks, mv.from, mv.num);
+        if self.flip_next {
+            elems.reverse();
+        }
+        self.flip_next = !self.flip_next;
+        stacks[mv.to - 1].append(&mut elems);
+    }
+
+    fn lifts(&self, _num: usize) -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::read_input;
+    use crate::simulate;
+
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    fn sample_stacks() -> Vec<Vec<char>> {
+        vec![vec!['A', 'B', 'C', 'D', 'E'], vec![]]
+    }
+
+    #[test]
+    fn test_limited_crane() {
+        let mut stacks = sample_stacks();
+        LimitedCrane { max_lift: NonZeroUsize::new(2).unwrap() }.execute(Move { num: 5, from: 1, to: 2 }, &mut stacks);
+        assert_eq!(stacks[1], vec!['D', 'E', 'B', 'C', 'A']);
+
+        let mut stacks = sample_stacks();
+        LimitedCrane { max_lift: NonZeroUsize::new(3).unwrap() }.execute(Move { num: 4, from: 1, to: 2 }, &mut stacks);
+        assert_eq!(stacks, vec![vec!['A'], vec!['C', 'D', 'E', 'B']]);
+    }
+
+    #[test]
+    fn test_limited_crane_matches_builtin_models() {
+        for path in [TEST_PATH, "../inputs/d05"] {
+            let (crates, moves) = read_input(path).unwrap();
+            assert_eq!(
+                simulate(&crates, &moves, &mut LimitedCrane { max_lift: NonZeroUsize::MIN }).unwrap(),
+                simulate(&crates, &moves, &mut CrateMover9000).unwrap()
+            );
+            assert_eq!(
+                simulate(&crates, &moves, &mut LimitedCrane { max_lift: NonZeroUsize::MAX }).unwrap(),
+                simulate(&crates, &moves, &mut CrateMover9001).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_same_stack_moves_change_nothing() {
+        for num in 1..=5 {
+            let mv = Move { num, from: 1, to: 1 };
+            let mut stacks = sample_stacks();
+            CrateMover9000.execute(mv, &mut stacks);
+            assert_eq!(stacks, sample_stacks());
+            CrateMover9001.execute(mv, &mut stacks);
+            assert_eq!(stacks, sample_stacks());
+            LimitedCrane { max_lift: NonZeroUsize::new(2).unwrap() }.execute(mv, &mut stacks);
+            assert_eq!(stacks, sample_stacks());
+        }
+    }
+
+    #[test]
+    fn test_alternating_crane() {
+        let mut stacks = sample_stacks();
+        let mut crane = AlternatingCrane::default();
+        crane.execute(Move { num: 2, from: 1, to: 2 }, &mut stacks);
+        assert_eq!(stacks[1], vec!['D', 'E']);
+        let after_one = crane;
+
+        crane.execute(Move { num: 2, from: 1, to: 2 }, &mut stacks);
+        assert_eq!(stacks[1], vec!['D', 'E', 'C', 'B']);
+
+        crane.execute(Move { num: 2, from: 2, to: 1 }, &mut stacks);
+        assert_eq!(stacks, vec![vec!['A', 'C', 'B'], vec!['D', 'E']]);
+        assert!(crane.flip_next);
+        assert_eq!(crane, after_one);
+    }
+}
@@ -0,0 +1,177 @@
+use crate::solve::format_moves;
+use crate::{Crates, Move};
+
+/// A small xorshift generator, so puzzles can be generated without pulling in a random crate.
+///
+/// The same seed always produces the same puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // Xorshift gets stuck at zero, so nudge that seed away from it.
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// The shape of a generated puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PuzzleSpec {
+    pub num_stacks: usize,
+    /// Every stack starts with `0..=max_height` crates.
+    pub max_height: usize,
+    pub num_moves: usize,
+    /// Every move lifts `1..=max_move` crates, fewer if the source stack is shorter.
+    pub max_move: usize,
+    /// Every label is `1..=max_label_len` letters long.
+    pub max_label_len: usize,
+}
+
+fn random_label(max_len: usize, rng: &mut Rng) -> String {
+    let len = 1 + rng.below(max_len);
+    (0..len).map(|_| char::from(b'A' + rng.below(26) as u8)).collect()
+}
+
+/// Generates a random drawing and a move list that is legal for any crane.
+///
+/// Labels are made of the letters `A` to `Z` and may repeat. If every stack starts out empty, no moves are generated.
+pub fn generate(spec: PuzzleSpec, rng: &mut Rng) -> (Crates, Vec<Move>) {
+    assert!(
+        spec.num_stacks > 0 && spec.max_move > 0 && spec.max_label_len > 0,
+        "a puzzle needs at least one stack, one crate per move and one letter per label"
+    );
+
+    let mut crates = Crates::new(spec.num_stacks);
+    for stack in crates.state.iter_mut() {
+        let height = rng.below(spec.max_height + 1);
+        stack.extend((0..height).map(|_| random_label(spec.max_label_len, rng)));
+    }
+
+    let mut heights = crates.state.iter().map(Vec::len).collect::<Vec<_>>();
+    let mut moves = vec![];
+    if heights.iter().all(|&height| height == 0) {
+        return (crates, moves);
+    }
+
+    while moves.len() < spec.num_moves {
+        let (from, to) = (rng.below(spec.num_stacks), rng.below(spec.num_stacks));
+        if heights[from] == 0 {
+            continue;
+        }
+
+        let num = 1 + rng.below(heights[from].min(spec.max_move));
+        heights[from] -= num;
+        heights[to] += num;
+        moves.push(Move { from: from + 1, to: to + 1, num });
+    }
+
+    (crates, moves)
+}
+
+/// Generates a puzzle in the input file's text format, ready for [`parse_input`](crate::parse::parse_input).
+pub fn generate_text(spec: PuzzleSpec, rng: &mut Rng) -> String {
+    let (crates, moves) = generate(spec, rng);
+    format!("{crates}\n\n{}", format_moves(&moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{Crane, CrateMover9000, CrateMover9001};
+    use crate::parse::parse_input;
+    use crate::{p1, p2};
+
+    const CASES: usize = 300;
+
+    fn random_spec(rng: &mut Rng) -> PuzzleSpec {
+        PuzzleSpec {
+            num_stacks: 1 + rng.below(12),
+            max_height: rng.below(20),
+            num_moves: rng.below(100),
+            max_move: 1 + rng.below(10),
+            max_label_len: 1 + rng.below(4),
+        }
+    }
+
+    fn crate_count(crates: &Crates) -> usize {
+        crates.state.iter().map(Vec::len).sum()
+    }
+
+    #[test]
+    fn test_generated_text_parses() {
+        let mut rng = Rng::new(5);
+        for _ in 0..CASES {
+            let spec = random_spec(&mut rng);
+            let mut text_rng = rng.clone();
+            let (crates, moves) = generate(spec, &mut rng);
+
+            let parsed = parse_input(&generate_text(spec, &mut text_rng)).unwrap();
+            assert_eq!(parsed, (crates.clone(), moves.clone()));
+            assert_eq!(crates.state.len(), spec.num_stacks);
+            assert!(crates.state.iter().all(|stack| stack.len() <= spec.max_height));
+            assert!(crates.state.iter().flatten().all(|label| (1..=spec.max_label_len).contains(&label.len())));
+            assert!(moves.iter().all(|mv| mv.num >= 1 && mv.num <= spec.max_move));
+            if crate_count(&crates) > 0 {
+                assert_eq!(moves.len(), spec.num_moves);
+            }
+        }
+    }
+
+    fn assert_conserves_crates<C: Crane>(crates: &Crates, moves: &[Move], mut crane: C) {
+        let total = crate_count(crates);
+        let mut end = crates.clone();
+        for &mv in moves {
+            end.apply(mv, &mut crane).unwrap();
+            assert_eq!(crate_count(&end), total);
+        }
+    }
+
+    #[test]
+    fn test_crate_count_is_conserved() {
+        let mut rng = Rng::new(10);
+        for _ in 0..CASES {
+            let (crates, moves) = generate(random_spec(&mut rng), &mut rng);
+            assert_conserves_crates(&crates, &moves, CrateMover9000);
+            assert_conserves_crates(&crates, &moves, CrateMover9001);
+        }
+    }
+
+    #[test]
+    fn test_single_crate_moves_agree() {
+        let mut rng = Rng::new(15);
+        for _ in 0..CASES {
+            let spec = PuzzleSpec { max_move: 1, max_label_len: 1, ..random_spec(&mut rng) };
+            let (crates, moves) = generate(spec, &mut rng);
+            assert!(moves.iter().all(|mv| mv.num == 1));
+            assert_eq!(p2(&crates, &moves).unwrap(), p1(&crates, &moves).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_crate_mover_9001_moves_are_invertible() {
+        let mut rng = Rng::new(20);
+        for _ in 0..CASES {
+            let (mut crates, moves) = generate(random_spec(&mut rng), &mut rng);
+            for mv in moves {
+                let before = crates.clone();
+                crates.apply(mv, &mut CrateMover9001).unwrap();
+
+                let mut restored = crates.clone();
+                restored.apply(Move { from: mv.to, to: mv.from, num: mv.num }, &mut CrateMover9001).unwrap();
+                assert_eq!(restored, before);
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::crane::Crane;
+use crate::{Crates, Move, MoveError};
+
+/// The state of the stacks after some number of moves.
+///
+/// Next to the labels, every crate carries the id it had in the initial state (assigned bottom to top, stack by
+/// stack), so two snapshots can tell which crate went where even when labels repeat.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub crates: Crates,
+    ids: Vec<Vec<usize>>,
+}
+
+/// Where a crate sits: a 1-based stack number and a 0-based height from the bottom of that stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub stack: usize,
+    pub height: usize,
+}
+
+/// A crate that sits on a different stack in two snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateChange {
+    pub id: usize,
+    pub label: String,
+    pub from: Location,
+    pub to: Location,
+}
+
+impl Snapshot {
+    fn new(crates: Crates) -> Snapshot {
+        let mut next_id = 0;
+        let ids = crates
+            .state
+            .iter()
+            .map(|stack| {
+                let ids = (next_id..next_id + stack.len()).collect::<Vec<_>>();
+                next_id += stack.len();
+                ids
+            })
+            .collect();
+
+        Snapshot { crates, ids }
+    }
+
+    fn locations(&self) -> Vec<(&str, Location)> {
+        let mut locations = vec![("", Location { stack: 0, height: 0 }); self.ids.iter().map(Vec::len).sum()];
+        for (stack_idx, (labels, ids)) in self.crates.state.iter().zip(&self.ids).enumerate() {
+            for (height, (label, &id)) in labels.iter().zip(ids).enumerate() {
+                locations[id] = (label, Location { stack: stack_idx + 1, height });
+            }
+        }
+        locations
+    }
+
+    /// Lists the crates that are on a different stack in `other`, ordered by crate id.
+    ///
+    /// Both snapshots must come from the same [`History`].
+    pub fn diff(&self, other: &Snapshot) -> Vec<CrateChange> {
+        self.locations()
+            .into_iter()
+            .zip(other.locations())
+            .enumerate()
+            .filter(|(_, ((_, from), (_, to)))| from.stack != to.stack)
+            .map(|(id, ((label, from), (_, to)))| CrateChange { id, label: label.to_string(), from, to })
+            .collect()
+    }
+}
+
+/// Steps through a move list while keeping every state it has visited, so it can go back, forward again, or jump
+/// to the state after any number of moves.
+///
+/// The crane is cloned along with each state, so stateful cranes like
+/// [`AlternatingCrane`](crate::crane::AlternatingCrane) replay correctly too.
+pub struct History<C: Crane + Clone> {
+    moves: Vec<Move>,
+    /// `states[i]` is the state after the first `i` moves; it only grows as far as we've stepped.
+    states: Vec<(Snapshot, C)>,
+    position: usize,
+}
+
+impl<C: Crane + Clone> History<C> {
+    pub fn new(crates: Crates, moves: Vec<Move>, crane: C) -> History<C> {
+        History { moves, states: vec![(Snapshot::new(crates), crane)], position: 0 }
+    }
+
+    /// The number of moves applied to reach the current state.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn current(&self) -> &Snapshot {
+        &self.states[self.position].0
+    }
+
+    /// Applies the next move. Returns `Ok(false)` when there are no moves left.
+    ///
+    /// An illegal move is reported and leaves the position where it was.
+    pub fn step_forward(&mut self) -> Result<bool, MoveError> {
+        if self.position == self.moves.len() {
+            return Ok(false);
+        }
+
+        if self.position + 1 == self.states.len() {
+            let move_idx = self.position;
+            let mv = self.moves[move_idx];
+            let (Snapshot { mut crates, mut ids }, mut crane) = self.states[move_idx].clone();
+
+            let mut id_crane = crane.clone();
+            crates.apply(mv, &mut crane).map_err(|underflow| MoveError { move_idx, mv, underflow })?;
+            id_crane.execute(mv, &mut ids);
+
+            self.states.push((Snapshot { crates, ids }, crane));
+        }
+
+        self.position += 1;
+        Ok(true)
+    }
+
+    /// Undoes the last move. Returns `false` when already at the initial state.
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        true
+    }
+
+    /// Jumps to the state after the first `position` moves (clamped to the move count).
+    ///
+    /// If an illegal move comes first, stops right before it and returns the error.
+    pub fn seek(&mut self, position: usize) -> Result<&Snapshot, MoveError> {
+        let position = position.min(self.moves.len());
+        if position < self.states.len() {
+            self.position = position;
+        } else {
+            self.position = self.states.len() - 1;
+            while self.position < position {
+                self.step_forward()?;
+            }
+        }
+
+        Ok(self.current())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{AlternatingCrane, CrateMover9000, CrateMover9001};
+    use crate::parse::read_input;
+
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    fn final_state<C: Crane>(crates: &Crates, moves: &[Move], mut crane: C) -> Crates {
+        let mut crates = crates.clone();
+        crates.apply_all(moves, &mut crane).unwrap();
+        crates
+    }
+
+    #[test]
+    fn test_seek_matches_forward_simulation() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+
+        let mut history = History::new(crates.clone(), moves.clone(), CrateMover9000);
+        for position in [4, 2, 0, 3, 1] {
+            let expected = final_state(&crates, &moves[..position], CrateMover9000);
+            assert_eq!(history.seek(position).unwrap().crates, expected);
+            assert_eq!(history.position(), position);
+        }
+
+        let mut history = History::new(crates.clone(), moves.clone(), CrateMover9001);
+        assert_eq!(
+            history.seek(10).unwrap().crates.get_top_crates(),
+            ["M", "C", "D"].map(|label| Some(label.to_string()))
+        );
+        assert_eq!(history.position(), 4);
+    }
+
+    #[test]
+    fn test_step_back_and_forward() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        let mut history = History::new(crates.clone(), moves.clone(), AlternatingCrane::default());
+
+        assert!(!history.step_back());
+        while history.step_forward().unwrap() {}
+        assert_eq!(history.position(), 4);
+        let end = history.current().clone();
+
+        assert!(history.step_back());
+        assert!(history.step_back());
+        assert_eq!(history.current().crates, final_state(&crates, &moves[..2], AlternatingCrane::default()));
+
+        assert!(history.step_forward().unwrap());
+        assert!(history.step_forward().unwrap());
+        assert!(!history.step_forward().unwrap());
+        assert_eq!(history.current(), &end);
+        assert_eq!(end.crates, final_state(&crates, &moves, AlternatingCrane::default()));
+    }
+
+    #[test]
+    fn test_seek_stops_before_illegal_move() {
+        let (crates, mut moves) = read_input(TEST_PATH).unwrap();
+        moves.insert(1, Move { num: 4, from: 3, to: 1 });
+
+        let mut history = History::new(crates, moves, CrateMover9001);
+        let err = history.seek(3).unwrap_err();
+        assert_eq!(err.move_idx, 1);
+        assert_eq!(history.position(), 1);
+    }
+
+    #[test]
+    fn test_diff() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+
+        let mut history = History::new(crates, moves, CrateMover9000);
+        let start = history.current().clone();
+        let after_one = history.seek(1).unwrap().clone();
+        assert_eq!(
+            start.diff(&after_one),
+            vec![CrateChange {
+                id: 4,
+                label: "D".to_string(),
+                from: Location { stack: 2, height: 2 },
+                to: Location { stack: 1, height: 2 }
+            }]
+        );
+
+        let after_two = history.seek(2).unwrap().clone();
+        let changes = start.diff(&after_two);
+        assert_eq!(changes.iter().map(|change| change.label.as_str()).collect::<String>(), "ZND");
+        assert!(changes.iter().all(|change| change.to.stack == 3));
+        assert_eq!(changes[0].to.height, 3);
+
+        assert!(start.diff(&start).is_empty());
+    }
+}
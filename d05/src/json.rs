@@ -0,0 +1,257 @@
+//! JSON import and export for puzzles and simulation results.
+//!
+//! A puzzle is an object with the stacks, each listed bottom first, and the moves with 1-based stack numbers:
+//!
+//! ```json
+//! {
+//!   "stacks": [["Z", "N"], ["M", "C", "D"], ["P"]],
+//!   "moves": [{ "num": 1, "from": 2, "to": 1 }]
+//! }
+//! ```
+//!
+//! A crate state alone is the same object without `"moves"`, and a move list alone is the bare `"moves"` array.
+//! Labels must be non-empty and free of spaces and brackets, so that every JSON puzzle can also be drawn.
+//!
+//! A [`Report`] describes one simulation run:
+//!
+//! ```json
+//! {
+//!   "top": ["C", "M", "Z"],
+//!   "final": { "stacks": [["C"], ["M"], ["P", "D", "N", "Z"]] },
+//!   "steps": [{ "move": { "num": 1, "from": 2, "to": 1 }, "moved": ["D"], "top": ["D", "C", "P"] }, ...]
+//! }
+//! ```
+//!
+//! `steps[i].moved` lists the crates that landed on the destination stack in move `i`, bottom first, and
+//! `steps[i].top` the top crates right after it. Both `"top"` lists have one entry per stack, `null` for an empty
+//! one. If a move is illegal, the run stops there: `"final"` is the state right before that move, and an `"error"`
+//! string describes it.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crane::Crane;
+use crate::{Crates, Move, MoveError};
+
+#[derive(Debug)]
+pub enum JsonError {
+    Syntax(serde_json::Error),
+    /// A label that is empty or contains a space or a bracket.
+    BadLabel {
+        stack: usize,
+        label: String,
+    },
+    /// A `from` or `to` stack that is `0` or greater than the number of stacks.
+    StackOutOfRange {
+        move_idx: usize,
+        index: usize,
+        num_stacks: usize,
+    },
+}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(err: serde_json::Error) -> Self {
+        JsonError::Syntax(err)
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Syntax(err) => write!(f, "{err}"),
+            JsonError::BadLabel { stack, label } => write!(f, "stack {stack} has an invalid label {label:?}"),
+            JsonError::StackOutOfRange { move_idx, index, num_stacks } => {
+                write!(f, "move #{move_idx}: stack {index} is out of range 1..={num_stacks}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+#[derive(Serialize, Deserialize)]
+struct Puzzle {
+    #[serde(flatten)]
+    crates: Crates,
+    moves: Vec<Move>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Step {
+    #[serde(rename = "move")]
+    pub mv: Move,
+    pub moved: Vec<String>,
+    pub top: Vec<Option<String>>,
+}
+
+/// The outcome of running a crane over a move list; see the module docs for its JSON form.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Report {
+    pub top: Vec<Option<String>>,
+    #[serde(rename = "final")]
+    pub final_state: Crates,
+    pub steps: Vec<Step>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn validate_crates(crates: &Crates) -> Result<(), JsonError> {
+    for (stack_idx, stack) in crates.state.iter().enumerate() {
+        if let Some(label) = stack.iter().find(|label| label.is_empty() || label.contains([' ', '[', ']'])) {
+            return Err(JsonError::BadLabel { stack: stack_idx + 1, label: label.clone() });
+        }
+    }
+    Ok(())
+}
+
+fn validate_moves(moves: &[Move], num_stacks: usize) -> Result<(), JsonError> {
+    for (move_idx, mv) in moves.iter().enumerate() {
+        if let Some(index) = [mv.from, mv.to].into_iter().find(|&index| index == 0 || index > num_stacks) {
+            return Err(JsonError::StackOutOfRange { move_idx, index, num_stacks });
+        }
+    }
+    Ok(())
+}
+
+pub fn crates_to_json(crates: &Crates) -> String {
+    serde_json::to_string_pretty(crates).unwrap()
+}
+
+pub fn crates_from_json(json: &str) -> Result<Crates, JsonError> {
+    let crates = serde_json::from_str(json)?;
+    validate_crates(&crates)?;
+    Ok(crates)
+}
+
+pub fn moves_to_json(moves: &[Move]) -> String {
+    serde_json::to_string_pretty(moves).unwrap()
+}
+
+/// Reads a bare move array, checking its stack numbers against `num_stacks`.
+pub fn moves_from_json(json: &str, num_stacks: usize) -> Result<Vec<Move>, JsonError> {
+    let moves: Vec<Move> = serde_json::from_str(json)?;
+    validate_moves(&moves, num_stacks)?;
+    Ok(moves)
+}
+
+pub fn puzzle_to_json(crates: &Crates, moves: &[Move]) -> String {
+    serde_json::to_string_pretty(&Puzzle { crates: crates.clone(), moves: moves.to_vec() }).unwrap()
+}
+
+pub fn puzzle_from_json(json: &str) -> Result<(Crates, Vec<Move>), JsonError> {
+    let Puzzle { crates, moves } = serde_json::from_str(json)?;
+    validate_crates(&crates)?;
+    validate_moves(&moves, crates.state.len())?;
+    Ok((crates, moves))
+}
+
+/// Runs `crane` over `moves`, recording every step, and stops at the first illegal move.
+pub fn report<C: Crane>(crates: &Crates, moves: &[Move], crane: &mut C) -> Report {
+    let mut state = crates.clone();
+    let mut steps = vec![];
+    let mut error = None;
+    for (move_idx, &mv) in moves.iter().enumerate() {
+        if let Err(underflow) = state.apply(mv, crane) {
+            error = Some(MoveError { move_idx, mv, underflow }.to_string());
+            break;
+        }
+
+        let destination = &state.state[mv.to - 1];
+        let moved = destination[destination.len() - mv.num..].to_vec();
+        steps.push(Step { mv, moved, top: state.get_top_crates() });
+    }
+
+    Report { top: state.get_top_crates(), final_state: state, steps, error }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::generate::{generate, PuzzleSpec, Rng};
+    use crate::parse::read_input;
+    use crate::simulate;
+
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    #[test]
+    fn test_puzzle_round_trip() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        let json = puzzle_to_json(&crates, &moves);
+        assert_eq!(puzzle_from_json(&json).unwrap(), (crates.clone(), moves.clone()));
+
+        assert_eq!(crates_from_json(&crates_to_json(&crates)).unwrap(), crates);
+        assert_eq!(moves_from_json(&moves_to_json(&moves), 3).unwrap(), moves);
+
+        let mut rng = Rng::new(12);
+        for _ in 0..50 {
+            let spec = PuzzleSpec { num_stacks: 6, max_height: 10, num_moves: 20, max_move: 5, max_label_len: 3 };
+            let (crates, moves) = generate(spec, &mut rng);
+            assert_eq!(puzzle_from_json(&puzzle_to_json(&crates, &moves)).unwrap(), (crates, moves));
+        }
+    }
+
+    #[test]
+    fn test_documented_schema() {
+        let json = r#"{
+            "stacks": [["Z", "N"], ["M", "C", "D"], ["P"]],
+            "moves": [{ "num": 1, "from": 2, "to": 1 }]
+        }"#;
+        let (crates, moves) = puzzle_from_json(json).unwrap();
+        assert_eq!(crates, read_input(TEST_PATH).unwrap().0);
+        assert_eq!(moves, vec![Move { num: 1, from: 2, to: 1 }]);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(matches!(puzzle_from_json(r#"{"stacks": [["A"]]}"#), Err(JsonError::Syntax(_))));
+        assert!(matches!(
+            crates_from_json(r#"{"stacks": [["A"], ["B C"]]}"#),
+            Err(JsonError::BadLabel { stack: 2, .. })
+        ));
+        assert!(matches!(crates_from_json(r#"{"stacks": [[""]]}"#), Err(JsonError::BadLabel { stack: 1, .. })));
+
+        let err = puzzle_from_json(r#"{"stacks": [["A"], []], "moves": [{"num": 1, "from": 1, "to": 3}]}"#);
+        assert_eq!(err.unwrap_err().to_string(), "move #0: stack 3 is out of range 1..=2");
+    }
+
+    #[test]
+    fn test_report() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        assert_eq!(
+            report(&crates, &moves, &mut CrateMover9001).top,
+            simulate(&crates, &moves, &mut CrateMover9001).unwrap()
+        );
+
+        let run = report(&crates, &moves, &mut CrateMover9000);
+        assert_eq!(run.top, simulate(&crates, &moves, &mut CrateMover9000).unwrap());
+        assert_eq!(run.steps.len(), 4);
+        assert_eq!(run.steps[0].mv, moves[0]);
+        assert_eq!(run.steps[0].moved, vec!["D"]);
+        assert_eq!(run.steps[0].top, ["D", "C", "P"].map(|label| Some(label.to_string())));
+        assert_eq!(run.steps[1].moved, vec!["D", "N", "Z"]);
+        assert_eq!(run.error, None);
+
+        let json = serde_json::to_value(&run).unwrap();
+        assert_eq!(json["top"], serde_json::json!(["C", "M", "Z"]));
+        assert_eq!(json["final"]["stacks"][2], serde_json::json!(["P", "D", "N", "Z"]));
+        assert_eq!(json["steps"][0]["move"], serde_json::json!({ "num": 1, "from": 2, "to": 1 }));
+        assert_eq!(json["steps"][1]["top"], serde_json::json!([null, "C", "Z"]));
+        assert!(json.get("error").is_none());
+    }
+
+    #[test]
+    fn test_report_stops_at_illegal_move() {
+        let (crates, mut moves) = read_input(TEST_PATH).unwrap();
+        moves.insert(2, Move { num: 5, from: 1, to: 2 });
+
+        let run = report(&crates, &moves, &mut CrateMover9001);
+        assert_eq!(run.steps.len(), 2);
+        assert_eq!(
+            run.error.as_deref(),
+            Some("move #2 (move 5 from 1 to 2): stack 1 holds 0 crates, but 5 were requested")
+        );
+        assert_eq!(run.final_state, report(&crates, &moves[..2], &mut CrateMover9001).final_state);
+    }
+}
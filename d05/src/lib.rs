@@ -0,0 +1,330 @@
+pub mod animate;
+pub mod backward;
+pub mod crane;
+pub mod generate;
+pub mod history;
+pub mod json;
+pub mod parse;
+pub mod solve;
+pub mod stats;
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crane::{Crane, CrateMover9000, CrateMover9001};
+
+/// Stacks of labeled crates, bottom first. Labels are any non-empty text without spaces or brackets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Crates {
+    #[serde(rename = "stacks")]
+    pub state: Vec<Vec<String>>,
+}
+
+impl Crates {
+    pub fn new(num_stacks: usize) -> Crates {
+        Crates { state: vec![vec![]; num_stacks] }
+    }
+
+    /// Returns the label of the top crate of every stack, or `None` for a stack that is empty.
+    pub fn get_top_crates(&self) -> Vec<Option<String>> {
+        self.state.iter().map(|stack| stack.last().cloned()).collect()
+    }
+
+    /// The width of each stack's column: wide enough for its longest `[label]` and its footer number.
+    fn column_widths(&self) -> Vec<usize> {
+        self.state
+            .iter()
+            .enumerate()
+            .map(|(stack_idx, stack)| {
+                let label_width = stack.iter().map(|label| label.chars().count()).max().unwrap_or(1);
+                (label_width + 2).max((stack_idx + 1).to_string().len() + 1)
+            })
+            .collect()
+    }
+
+    /// Draws the stacks in the puzzle's format, rendering each crate with `cell(stack index, height, label)`.
+    ///
+    /// Columns are as wide as their longest label, and `cell` should draw a crate as wide as `[label]`.
+    pub fn draw(&self, out: &mut impl fmt::Write, cell: impl Fn(usize, usize, &str) -> String) -> fmt::Result {
+        let widths = self.column_widths();
+        let height = self.state.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self
+                .state
+                .iter()
+                .enumerate()
+                .map(|(stack_idx, stack)| match stack.get(level) {
+                    Some(label) => {
+                        let padding = widths[stack_idx] - label.chars().count() - 2;
+                        format!("{}{}", cell(stack_idx, level, label), " ".repeat(padding))
+                    }
+                    None => " ".repeat(widths[stack_idx]),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(out, "{}", row.trim_end())?;
+        }
+
+        let footer = widths
+            .iter()
+            .enumerate()
+            .map(|(stack_idx, &width)| format!(" {:<w$}", stack_idx + 1, w = width - 1))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(out, "{}", footer.trim_end())
+    }
+
+    /// Applies a single move, leaving the state untouched if the source stack holds fewer than `mv.num` crates.
+    pub fn apply<C: Crane>(&mut self, mv: Move, crane: &mut C) -> Result<(), StackUnderflow> {
+        let available = self.state[mv.from - 1].len();
+        if available < mv.num {
+            return Err(StackUnderflow { stack: mv.from, requested: mv.num, available });
+        }
+
+        crane.execute(mv, &mut self.state);
+        Ok(())
+    }
+
+    /// Applies `moves` in order, stopping at the first illegal one.
+    ///
+    /// On error, `self` holds the state right before the failing move.
+    pub fn apply_all<C: Crane>(&mut self, moves: &[Move], crane: &mut C) -> Result<(), MoveError> {
+        for (move_idx, &mv) in moves.iter().enumerate() {
+            self.apply(mv, crane).map_err(|underflow| MoveError { move_idx, mv, underflow })?;
+        }
+        Ok(())
+    }
+}
+
+/// Draws the stacks in the puzzle's format, numbered footer included, so the output parses back into the same value.
+impl fmt::Display for Crates {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.draw(f, |_, _, label| format!("[{label}]"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub num: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.num, self.from, self.to)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackUnderflow {
+    pub stack: usize,
+    pub requested: usize,
+    pub available: usize,
+}
+
+impl fmt::Display for StackUnderflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let StackUnderflow { stack, requested, available } = self;
+        write!(f, "stack {stack} holds {available} crates, but {requested} were requested")
+    }
+}
+
+impl std::error::Error for StackUnderflow {}
+
+/// A [`StackUnderflow`] together with the 0-based index of the move that caused it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MoveError {
+    pub move_idx: usize,
+    pub mv: Move,
+    pub underflow: StackUnderflow,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move #{} ({}): {}", self.move_idx, self.mv, self.underflow)
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Runs `crane` over `moves`, returning the top crates of the final state, or the first illegal move.
+pub fn simulate<C: Crane>(crates: &Crates, moves: &[Move], crane: &mut C) -> Result<Vec<Option<String>>, MoveError> {
+    let mut crates = crates.clone();
+    crates.apply_all(moves, crane)?;
+    Ok(crates.get_top_crates())
+}
+
+pub fn p1(crates: &Crates, moves: &[Move]) -> Result<String, MoveError> {
+    Ok(simulate(crates, moves, &mut CrateMover9000)?.into_iter().flatten().collect())
+}
+
+pub fn p2(crates: &Crates, moves: &[Move]) -> Result<String, MoveError> {
+    Ok(simulate(crates, moves, &mut CrateMover9001)?.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_input, read_input};
+
+    const INPUT_PATH: &str = "../inputs/d05";
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    fn tops(labels: &[Option<&str>]) -> Vec<Option<String>> {
+        labels.iter().map(|label| label.map(String::from)).collect()
+    }
+
+    /// Builds stacks from space-separated labels, bottom first.
+    fn from_labels(stacks: &[&str]) -> Crates {
+        Crates { state: stacks.iter().map(|stack| stack.split_whitespace().map(String::from).collect()).collect() }
+    }
+
+    #[test]
+    fn test_input_parsing_test() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+
+        assert_eq!(crates.state[0], vec!["Z", "N"]);
+        assert_eq!(crates.state[1], vec!["M", "C", "D"]);
+        assert_eq!(crates.state[2], vec!["P"]);
+
+        assert_eq!(moves.len(), 4);
+        assert_eq!(&moves[0], &Move { num: 1, from: 2, to: 1 });
+        assert_eq!(&moves[1], &Move { num: 3, from: 1, to: 3 });
+    }
+
+    #[test]
+    fn test_p1() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        let test_ans = p1(&crates, &moves).unwrap();
+        assert_eq!(test_ans, "CMZ");
+
+        let (crates, moves) = read_input(INPUT_PATH).unwrap();
+        let ans = p1(&crates, &moves).unwrap();
+        assert_eq!(ans, "BWNCQRMDB");
+    }
+
+    #[test]
+    fn test_p2() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        let test_ans = p2(&crates, &moves).unwrap();
+        assert_eq!(test_ans, "MCD");
+
+        let (crates, moves) = read_input(INPUT_PATH).unwrap();
+        let ans = p2(&crates, &moves).unwrap();
+        assert_eq!(ans, "NHWZCBNBF");
+    }
+
+    #[test]
+    fn test_stack_count_from_footer() {
+        let (crates, _) = read_input(TEST_PATH).unwrap();
+        assert_eq!(crates.state.len(), 3);
+
+        let (crates, _) = read_input(INPUT_PATH).unwrap();
+        assert_eq!(crates.state.len(), 9);
+
+        let (crates, moves) =
+            parse_input("[A]         [E]\n[B] [C] [D] [F] [G]\n 1   2   3   4   5\n\nmove 1 from 4 to 2").unwrap();
+        assert_eq!(crates.state.len(), 5);
+        assert_eq!(crates.state[3], vec!["F", "E"]);
+        assert_eq!(p1(&crates, &moves).unwrap(), "AEDFG");
+    }
+
+    #[test]
+    fn test_apply() {
+        let (mut crates, _) = read_input(TEST_PATH).unwrap();
+        crates.apply(Move { num: 2, from: 2, to: 3 }, &mut CrateMover9000).unwrap();
+        assert_eq!(crates.state[2], vec!["P", "D", "C"]);
+
+        crates.apply(Move { num: 3, from: 3, to: 1 }, &mut CrateMover9001).unwrap();
+        assert_eq!(crates.state[0], vec!["Z", "N", "P", "D", "C"]);
+        assert!(crates.state[2].is_empty());
+    }
+
+    #[test]
+    fn test_same_stack_move() {
+        let (crates, moves) = parse_input("[A]\n[B]\n[C] [D]\n 1   2\n\nmove 2 from 1 to 1").unwrap();
+        assert_eq!(p1(&crates, &moves).unwrap(), "AD");
+        assert_eq!(p2(&crates, &moves).unwrap(), "AD");
+    }
+
+    #[test]
+    fn test_illegal_move_is_an_error() {
+        let (crates, moves) = parse_input("[A]\n[B] [C]\n 1   2\n\nmove 5 from 1 to 2").unwrap();
+        let err = p1(&crates, &moves).unwrap_err();
+        assert_eq!(err.underflow, StackUnderflow { stack: 1, requested: 5, available: 2 });
+        assert_eq!(p2(&crates, &moves).unwrap_err(), err);
+    }
+
+    #[test]
+    fn test_apply_underflow() {
+        let (mut crates, _) = read_input(TEST_PATH).unwrap();
+        let before = crates.clone();
+
+        let err = crates.apply(Move { num: 2, from: 3, to: 1 }, &mut CrateMover9001).unwrap_err();
+        assert_eq!(err, StackUnderflow { stack: 3, requested: 2, available: 1 });
+        assert_eq!(crates, before);
+    }
+
+    #[test]
+    fn test_apply_all_stops_at_first_illegal_move() {
+        let (mut crates, mut moves) = read_input(TEST_PATH).unwrap();
+        moves.insert(2, Move { num: 5, from: 1, to: 2 });
+
+        let mut expected = crates.clone();
+        expected.apply_all(&moves[..2], &mut CrateMover9000).unwrap();
+
+        let err = crates.apply_all(&moves, &mut CrateMover9000).unwrap_err();
+        assert_eq!(err.move_idx, 2);
+        assert_eq!(err.underflow, StackUnderflow { stack: 1, requested: 5, available: 0 });
+        assert_eq!(err.to_string(), "move #2 (move 5 from 1 to 2): stack 1 holds 0 crates, but 5 were requested");
+        assert_eq!(crates, expected);
+    }
+
+    #[test]
+    fn test_display_matches_input_drawing() {
+        for path in [TEST_PATH, INPUT_PATH] {
+            let content = std::fs::read_to_string(path).unwrap();
+            let drawing = content.split("\n\n").next().unwrap();
+
+            let (crates, _) = read_input(path).unwrap();
+            assert_eq!(crates.to_string(), drawing);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let (mut crates, moves) = read_input(INPUT_PATH).unwrap();
+        for (move_idx, &mv) in moves.iter().enumerate() {
+            crates.apply(mv, &mut CrateMover9001).unwrap();
+            if move_idx % 50 == 0 {
+                let (parsed, parsed_moves) = parse_input(&crates.to_string()).unwrap();
+                assert_eq!(parsed, crates);
+                assert!(parsed_moves.is_empty());
+            }
+        }
+
+        let sparse = from_labels(&["", "A B", "", "C", ""]);
+        assert_eq!(sparse.to_string(), "    [B]\n    [A]     [C]\n 1   2   3   4   5");
+        assert_eq!(parse_input(&sparse.to_string()).unwrap().0, sparse);
+
+        let wide = Crates { state: (0..12).map(|n| vec![char::from(b'A' + n).to_string()]).collect() };
+        assert_eq!(parse_input(&wide.to_string()).unwrap().0, wide);
+    }
+
+    #[test]
+    fn test_multi_character_labels() {
+        let multi = from_labels(&["AB C", "12", "X LONG"]);
+        assert_eq!(multi.to_string(), "[C]       [LONG]\n[AB] [12] [X]\n 1    2    3");
+        assert_eq!(parse_input(&multi.to_string()).unwrap().0, multi);
+        assert_eq!(multi.get_top_crates(), tops(&[Some("C"), Some("12"), Some("LONG")]));
+
+        let content = "[AB]      [XYZ]\n[CD] [E]  [W]\n 1    2    3\n\nmove 2 from 1 to 3\nmove 1 from 2 to 1";
+        let (start, moves) = parse_input(content).unwrap();
+        assert_eq!(start, from_labels(&["CD AB", "E", "W XYZ"]));
+        assert_eq!(simulate(&start, &moves, &mut CrateMover9000).unwrap(), tops(&[Some("E"), None, Some("CD")]));
+        assert_eq!(simulate(&start, &moves, &mut CrateMover9001).unwrap(), tops(&[Some("E"), None, Some("AB")]));
+        assert_eq!(p1(&start, &moves).unwrap(), "ECD");
+    }
+}
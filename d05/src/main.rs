@@ -1,156 +1,183 @@
-use std::fs;
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-struct Crates<const N: usize> {
-    pub state: [Vec<char>; N],
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::process;
+use std::time::Duration;
+
+use d05::animate::{Animation, Style};
+use d05::crane::{Crane, CrateMover9000, CrateMover9001};
+use d05::parse::read_input;
+use d05::{json, solve, stats};
+use d05::{p1, p2, Crates, Move};
+
+const INPUT_PATH: &str = "../inputs/d05";
+const MAX_SOLVER_STATES: usize = 5_000_000;
+
+const USAGE: &str =
+    "usage: d05 [INPUT] [--json] [--stats | --animate [--delay MS] [--frames PATH] | --solve TARGET] [--crane 9000|9001]
+
+  INPUT          a puzzle drawing, or a JSON puzzle if it ends in .json (default: ../inputs/d05)
+  --json         print both parts' results as JSON instead of text
+  --stats        print per-stack heights, source and destination counts and crane lifts for both parts
+  --animate      replay the moves, redrawing the stacks before each one
+  --solve        print a shortest move list from INPUT's drawing to TARGET's drawing
+  --crane        crane model to animate or solve with (default: 9001)
+  --delay        milliseconds to wait between frames (default: 300, or 0 with --frames)
+  --frames       write plain-text frames to PATH instead of the terminal";
+
+/// The crane models `--crane` can pick, so the choice is made once and shared by `--animate` and `--solve`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+enum CraneModel {
+    Mover9000,
+    #[default]
+    Mover9001,
 }
 
-impl<const N: usize> Crates<N> {
-    pub fn get_top_crates(&self) -> String {
-        let mut ans = String::new();
-        for cr in &self.state {
-            ans.push(*cr.last().unwrap());
+impl CraneModel {
+    fn parse(name: &str) -> Result<CraneModel, String> {
+        match name {
+            "9000" => Ok(CraneModel::Mover9000),
+            "9001" => Ok(CraneModel::Mover9001),
+            other => Err(format!("unknown crane {other:?}, expected 9000 or 9001")),
         }
-        ans
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Move {
-    from: usize,
-    to: usize,
-    num: usize,
-}
-
-fn p1<const N: usize>(crates: &Crates<N>, moves: &Vec<Move>) -> String {
-    let mut crates = crates.clone();
-    for mv in moves {
-        for _ in 0..mv.num {
-            let x = crates.state[mv.from - 1].pop().unwrap();
-            crates.state[mv.to - 1].push(x);
+impl Crane for CraneModel {
+    fn execute<T>(&mut self, mv: Move, stacks: &mut [Vec<T>]) {
+        match self {
+            CraneModel::Mover9000 => CrateMover9000.execute(mv, stacks),
+            CraneModel::Mover9001 => CrateMover9001.execute(mv, stacks),
         }
     }
 
-    crates.get_top_crates()
+    fn lifts(&self, num: usize) -> usize {
+        match self {
+            CraneModel::Mover9000 => CrateMover9000.lifts(num),
+            CraneModel::Mover9001 => CrateMover9001.lifts(num),
+        }
+    }
 }
 
-fn p2<const N: usize>(crates: &Crates<N>, moves: &Vec<Move>) -> String {
-    let mut crates = crates.clone();
-    for mv in moves {
-        let source_crate = &mut crates.state[mv.from - 1];
-        let start_idx = source_crate.len() - mv.num;
-        let mut elems = source_crate.drain(start_idx..).collect::<Vec<_>>();
+#[derive(Debug, Default)]
+struct Args {
+    input: Option<String>,
+    json: bool,
+    stats: bool,
+    animate: bool,
+    crane: CraneModel,
+    delay: Option<u64>,
+    frames: Option<String>,
+    solve: Option<String>,
+}
 
-        crates.state[mv.to - 1].append(&mut elems);
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut it = env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--json" => args.json = true,
+            "--stats" => args.stats = true,
+            "--animate" => args.animate = true,
+            "--crane" => args.crane = CraneModel::parse(&value()?)?,
+            "--delay" => args.delay = Some(value()?.parse().map_err(|_| "--delay must be a number".to_string())?),
+            "--frames" => args.frames = Some(value()?),
+            "--solve" => args.solve = Some(value()?),
+            "-h" | "--help" => return Err(String::new()),
+            _ if !arg.starts_with('-') && args.input.is_none() => args.input = Some(arg),
+            _ => return Err(format!("unexpected argument {arg:?}")),
+        }
     }
-
-    crates.get_top_crates()
+    Ok(args)
 }
 
-fn read_input<const N: usize>(path: &str) -> (Crates<N>, Vec<Move>) {
-    let content = fs::read_to_string(path).unwrap();
-    let mut lines = content.trim_end().split("\n");
-    let mut line = lines.next().unwrap();
-
-    const EMPTY: Vec<char> = vec![];
-    let mut crates = Crates { state: [EMPTY; N] };
-    while !line.starts_with(" 1") {
-        let chars = line.chars().collect::<Vec<_>>();
-        for crate_id in 0..N {
-            if let Some(&ch) = chars.get(crate_id * 4 + 1) {
-                if ch != ' ' {
-                    crates.state[crate_id].push(ch);
-                }
-            }
+fn animate(args: &Args, crates: &Crates, moves: &[Move]) -> Result<(), String> {
+    let (style, default_delay) = if args.frames.is_some() { (Style::Headless, 0) } else { (Style::Terminal, 300) };
+    let animation = Animation { style, delay: Duration::from_millis(args.delay.unwrap_or(default_delay)) };
+    let mut crane = args.crane;
+
+    let result = match &args.frames {
+        Some(path) => {
+            let file = File::create(path).map_err(|err| format!("{path}: {err}"))?;
+            animation.run(crates, moves, &mut crane, &mut BufWriter::new(file))
         }
+        None => animation.run(crates, moves, &mut crane, &mut io::stdout().lock()),
+    };
+    result.map(|_| ()).map_err(|err| err.to_string())
+}
 
-        line = lines.next().unwrap();
-    }
+fn solve(target_path: &str, crates: &Crates, crane: CraneModel) -> Result<(), String> {
+    let (target, _) = read_puzzle(target_path)?;
+    let moves = solve::solve(crates, &target, crane, MAX_SOLVER_STATES).map_err(|err| err.to_string())?;
+    print!("{}", solve::format_moves(&moves));
+    Ok(())
+}
 
-    for crate_id in 0..N {
-        crates.state[crate_id].reverse();
+fn print_stats(crates: &Crates, moves: &[Move], as_json: bool) -> Result<(), String> {
+    let (_, p1_stats, p1_err) = stats::simulate_with_stats(crates, moves, &mut CrateMover9000);
+    let (_, p2_stats, p2_err) = stats::simulate_with_stats(crates, moves, &mut CrateMover9001);
+    if as_json {
+        println!("{:#}", serde_json::json!({ "p1": p1_stats, "p2": p2_stats }));
+    } else {
+        println!("CrateMover 9000:\n{p1_stats}\n\nCrateMover 9001:\n{p2_stats}");
     }
 
-    lines.next();
-    let mut moves = vec![];
-    for line in lines {
-        let mut it = line.split_ascii_whitespace();
-        it.next();
-        let num: usize = it.next().unwrap().parse().unwrap();
-        it.next();
-        let from: usize = it.next().unwrap().parse().unwrap();
-        it.next();
-        let to: usize = it.next().unwrap().parse().unwrap();
-
-        moves.push(Move { from, to, num });
+    match p1_err.or(p2_err) {
+        Some(err) => Err(err.to_string()),
+        None => Ok(()),
     }
+}
 
-    (crates, moves)
+fn read_puzzle(path: &str) -> Result<(Crates, Vec<Move>), String> {
+    if path.ends_with(".json") {
+        let content = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+        json::puzzle_from_json(&content).map_err(|err| format!("{path}: {err}"))
+    } else {
+        read_input(path).map_err(|err| format!("{path}: {err}"))
+    }
 }
 
-const INPUT_PATH: &'static str = "../inputs/d05";
+fn run(args: Args) -> Result<(), String> {
+    let path = args.input.as_deref().unwrap_or(INPUT_PATH);
+    let (crates, moves) = read_puzzle(path)?;
 
-fn main() {
-    let (crates, moves) = read_input::<9>(INPUT_PATH);
-    let p1_ans = p1(&crates, &moves);
-    println!("P1: {p1_ans}.");
+    if args.stats {
+        return print_stats(&crates, &moves, args.json);
+    }
 
-    let p2_ans = p2(&crates, &moves);
-    println!("P2: {p2_ans}.");
-}
+    if args.json {
+        let reports = serde_json::json!({
+            "p1": json::report(&crates, &moves, &mut CrateMover9000),
+            "p2": json::report(&crates, &moves, &mut CrateMover9001),
+        });
+        println!("{reports:#}");
+        return Ok(());
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    const TEST_PATH: &'static str = "../inputs/d05_test";
-
-    #[test]
-    fn test_input_parsing_test() {
-        let (crates, moves) = read_input::<3>(TEST_PATH);
-
-        assert_eq!(crates.state[0], vec!['Z', 'N']);
-        assert_eq!(crates.state[1], vec!['M', 'C', 'D']);
-        assert_eq!(crates.state[2], vec!['P']);
-
-        assert_eq!(moves.len(), 4);
-        assert_eq!(
-            &moves[0],
-            &Move {
-                num: 1,
-                from: 2,
-                to: 1
-            }
-        );
-        assert_eq!(
-            &moves[1],
-            &Move {
-                num: 3,
-                from: 1,
-                to: 3
-            }
-        );
+    if let Some(target_path) = &args.solve {
+        return solve(target_path, &crates, args.crane);
     }
 
-    #[test]
-    fn test_p1() {
-        let (crates, moves) = read_input::<3>(TEST_PATH);
-        let test_ans = p1(&crates, &moves);
-        assert_eq!(test_ans, "CMZ");
+    if !args.animate {
+        let p1_ans = p1(&crates, &moves).map_err(|err| format!("{path}: {err}"))?;
+        println!("P1: {p1_ans}.");
 
-        let (crates, moves) = read_input::<9>(INPUT_PATH);
-        let ans = p1(&crates, &moves);
-        assert_eq!(ans, "BWNCQRMDB");
+        let p2_ans = p2(&crates, &moves).map_err(|err| format!("{path}: {err}"))?;
+        println!("P2: {p2_ans}.");
+        return Ok(());
     }
 
-    #[test]
-    fn test_p2() {
-        let (crates, moves) = read_input::<3>(TEST_PATH);
-        let test_ans = p2(&crates, &moves);
-        assert_eq!(test_ans, "MCD");
+    animate(&args, &crates, &moves)
+}
 
-        let (crates, moves) = read_input::<9>(INPUT_PATH);
-        let ans = p2(&crates, &moves);
-        assert_eq!(ans, "NHWZCBNBF");
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(err) = result {
+        if !err.is_empty() {
+            eprintln!("{err}\n");
+        }
+        eprintln!("{USAGE}");
+        process::exit(2);
     }
 }
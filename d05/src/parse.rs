@@ -0,0 +1,376 @@
+use std::fmt;
+use std::fs;
+use std::io;
+
+use crate::{Crates, Move};
+
+/// A problem found while parsing a crate drawing and its move list.
+///
+/// `line` and `column` are 1-based and point at the offending character in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The drawing ended before the ` 1   2   3 ...` footer.
+    MissingFooter,
+    /// A footer label is not the next stack number.
+    BadFooterLabel { expected: usize, found: String },
+    /// A drawing row has crates to the right of the last stack in the footer.
+    RowWiderThanFooter { num_stacks: usize },
+    /// A `[` or `]` that doesn't sit on a stack's column, a bracket without its pair, or an empty label.
+    MisalignedBracket,
+    /// The footer is not followed by an empty line.
+    MissingSeparator,
+    /// A move line that isn't shaped like `move N from A to B`.
+    MalformedMove,
+    /// A move field that should be a number but isn't.
+    NonNumeric { field: &'static str, found: String },
+    /// A `from` or `to` stack that is `0` or greater than the number of stacks.
+    StackOutOfRange { index: usize, num_stacks: usize },
+}
+
+impl ParseError {
+    fn new(line_idx: usize, col_idx: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError { line: line_idx + 1, column: col_idx + 1, kind }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::MissingFooter => write!(f, "crate drawing has no numbered footer line"),
+            ParseErrorKind::BadFooterLabel { expected, found } => {
+                write!(f, "expected footer label {expected}, found {found:?}")
+            }
+            ParseErrorKind::RowWiderThanFooter { num_stacks } => {
+                write!(f, "row is wider than the footer's {num_stacks} stacks")
+            }
+            ParseErrorKind::MisalignedBracket => write!(f, "crate bracket is not aligned with a stack column"),
+            ParseErrorKind::MissingSeparator => write!(f, "expected an empty line between the drawing and the moves"),
+            ParseErrorKind::MalformedMove => write!(f, "expected `move N from A to B`"),
+            ParseErrorKind::NonNumeric { field, found } => write!(f, "{field} should be a number, found {found:?}"),
+            ParseErrorKind::StackOutOfRange { index, num_stacks } => {
+                write!(f, "stack {index} is out of range 1..={num_stacks}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A puzzle file that couldn't be read, or whose content didn't parse.
+#[derive(Debug)]
+pub enum InputError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl From<io::Error> for InputError {
+    fn from(err: io::Error) -> Self {
+        InputError::Io(err)
+    }
+}
+
+impl From<ParseError> for InputError {
+    fn from(err: ParseError) -> Self {
+        InputError::Parse(err)
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io(err) => write!(f, "{err}"),
+            InputError::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+pub fn read_input(path: &str) -> Result<(Crates, Vec<Move>), InputError> {
+    Ok(parse_input(&fs::read_to_string(path)?)?)
+}
+
+pub fn parse_input(content: &str) -> Result<(Crates, Vec<Move>), ParseError> {
+    // Hand-edited files may have Windows line endings.
+    let lines = content.trim_end().split('\n').map(|line| line.strip_suffix('\r').unwrap_or(line)).collect::<Vec<_>>();
+
+    let footer_idx = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with(|ch: char| ch.is_ascii_digit()))
+        .filter(|&idx| lines[..idx].iter().all(|line| !line.trim().is_empty()))
+        .ok_or_else(|| {
+            let line_idx = lines.iter().position(|line| line.trim().is_empty()).unwrap_or(lines.len());
+            ParseError::new(line_idx, 0, ParseErrorKind::MissingFooter)
+        })?;
+
+    let footer = parse_footer(footer_idx, lines[footer_idx])?;
+    let num_stacks = footer.len();
+    let mut crates = Crates::new(num_stacks);
+    for row_idx in (0..footer_idx).rev() {
+        for (crate_id, label) in parse_row(row_idx, lines[row_idx], &footer)? {
+            crates.state[crate_id].push(label);
+        }
+    }
+
+    let separator_idx = footer_idx + 1;
+    match lines.get(separator_idx) {
+        Some(line) if !line.trim().is_empty() => {
+            return Err(ParseError::new(separator_idx, 0, ParseErrorKind::MissingSeparator));
+        }
+        _ => (),
+    }
+
+    let mut moves = vec![];
+    for (line_idx, &line) in lines.iter().enumerate().skip(separator_idx + 1) {
+        moves.push(parse_move(line_idx, line, num_stacks)?);
+    }
+
+    Ok((crates, moves))
+}
+
+/// Parses the ` 1   2   3 ...` footer, returning the columns each stack's number spans (first and last, inclusive).
+///
+/// The number of spans is the number of stacks.
+fn parse_footer(line_idx: usize, footer: &str) -> Result<Vec<(usize, usize)>, ParseError> {
+    let mut spans = vec![];
+    for (col_idx, label) in tokens(footer) {
+        match label.parse::<usize>() {
+            Ok(n) if n == spans.len() + 1 => spans.push((col_idx, col_idx + label.len() - 1)),
+            _ => {
+                let kind = ParseErrorKind::BadFooterLabel { expected: spans.len() + 1, found: label.to_string() };
+                return Err(ParseError::new(line_idx, col_idx, kind));
+            }
+        }
+    }
+    Ok(spans)
+}
+
+/// Returns `(stack index, label)` for every crate drawn in a `[A]     [C]` row.
+///
+/// A crate belongs to the stack whose footer number sits under its brackets, so columns can be as wide as their
+/// labels need.
+fn parse_row(line_idx: usize, row: &str, footer: &[(usize, usize)]) -> Result<Vec<(usize, String)>, ParseError> {
+    let chars = row.chars().collect::<Vec<_>>();
+    let misaligned = |col_idx| ParseError::new(line_idx, col_idx, ParseErrorKind::MisalignedBracket);
+    let last_footer_col = footer.last().map_or(0, |&(_, end)| end);
+
+    let mut row_crates: Vec<(usize, String)> = vec![];
+    let mut col_idx = 0;
+    while col_idx < chars.len() {
+        if chars[col_idx] == ' ' {
+            col_idx += 1;
+            continue;
+        }
+        if chars[col_idx] != '[' || (col_idx > 0 && chars[col_idx - 1] != ' ') {
+            return Err(misaligned(col_idx));
+        }
+
+        let close_idx = match chars[col_idx + 1..].iter().position(|&ch| matches!(ch, '[' | ']' | ' ')) {
+            Some(offset) if offset > 0 && chars[col_idx + 1 + offset] == ']' => col_idx + 1 + offset,
+            Some(offset) => return Err(misaligned(col_idx + 1 + offset)),
+            None => return Err(misaligned(chars.len())),
+        };
+
+        let mut stacks = footer.iter().enumerate().filter(|(_, &(start, end))| start <= close_idx && col_idx <= end);
+        let crate_id = match (stacks.next(), stacks.next()) {
+            (Some((crate_id, _)), None) => crate_id,
+            (None, _) if col_idx > last_footer_col => {
+                let kind = ParseErrorKind::RowWiderThanFooter { num_stacks: footer.len() };
+                return Err(ParseError::new(line_idx, col_idx, kind));
+            }
+            _ => return Err(misaligned(col_idx)),
+        };
+        if row_crates.last().is_some_and(|&(prev_id, _)| prev_id >= crate_id) {
+            return Err(misaligned(col_idx));
+        }
+
+        row_crates.push((crate_id, chars[col_idx + 1..close_idx].iter().collect()));
+        col_idx = close_idx + 1;
+    }
+
+    Ok(row_crates)
+}
+
+fn parse_move(line_idx: usize, line: &str, num_stacks: usize) -> Result<Move, ParseError> {
+    let tokens = tokens(line);
+    let malformed = |col_idx| ParseError::new(line_idx, col_idx, ParseErrorKind::MalformedMove);
+
+    for (token_idx, keyword) in [(0, "move"), (2, "from"), (4, "to")] {
+        match tokens.get(token_idx) {
+            Some(&(_, token)) if token == keyword => (),
+            Some(&(col_idx, _)) => return Err(malformed(col_idx)),
+            None => return Err(malformed(line.len())),
+        }
+    }
+    if let Some(&(col_idx, _)) = tokens.get(6) {
+        return Err(malformed(col_idx));
+    }
+
+    let number = |token_idx: usize, field: &'static str| {
+        let (col_idx, token) = tokens.get(token_idx).copied().ok_or_else(|| malformed(line.len()))?;
+        token.parse::<usize>().map(|n| (col_idx, n)).map_err(|_| {
+            ParseError::new(line_idx, col_idx, ParseErrorKind::NonNumeric { field, found: token.to_string() })
+        })
+    };
+    let stack = |token_idx: usize, field: &'static str| {
+        let (col_idx, index) = number(token_idx, field)?;
+        if index == 0 || index > num_stacks {
+            return Err(ParseError::new(line_idx, col_idx, ParseErrorKind::StackOutOfRange { index, num_stacks }));
+        }
+        Ok(index)
+    };
+
+    let (_, num) = number(1, "count")?;
+    let from = stack(3, "from")?;
+    let to = stack(5, "to")?;
+    Ok(Move { from, to, num })
+}
+
+/// Splits a line on whitespace, keeping the 0-based column each token starts at.
+fn tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (col_idx, ch) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (ch.is_whitespace(), start) {
+            (false, None) => start = Some(col_idx),
+            (true, Some(start_idx)) => {
+                tokens.push((start_idx, &line[start_idx..col_idx]));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(content: &str) -> ParseError {
+        parse_input(content).unwrap_err()
+    }
+
+    #[test]
+    fn test_valid_drawing() {
+        let (crates, moves) = parse_input("    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1").unwrap();
+        assert_eq!(crates.state, vec![vec!["Z", "N"], vec!["M", "C", "D"], vec!["P"]]);
+        assert_eq!(moves, vec![Move { num: 1, from: 2, to: 1 }]);
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let content = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\n";
+        let crlf = content.replace('\n', "\r\n");
+        assert_eq!(parse_input(&crlf).unwrap(), parse_input(content).unwrap());
+
+        let err = parse_err("[A] [B]\r\n 1   2\r\n\r\nmove 1 from 1 to 3\r\n");
+        assert_eq!(
+            err,
+            ParseError { line: 4, column: 18, kind: ParseErrorKind::StackOutOfRange { index: 3, num_stacks: 2 } }
+        );
+    }
+
+    #[test]
+    fn test_missing_footer() {
+        let err = parse_err("[A] [B]\n\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 2, column: 1, kind: ParseErrorKind::MissingFooter });
+    }
+
+    #[test]
+    fn test_bad_footer_label() {
+        let err = parse_err("[A] [B] [C]\n 1   2   4\n\nmove 1 from 1 to 2");
+        assert_eq!((err.line, err.column), (2, 10));
+        assert_eq!(err.kind, ParseErrorKind::BadFooterLabel { expected: 3, found: "4".to_string() });
+    }
+
+    #[test]
+    fn test_row_wider_than_footer() {
+        let err = parse_err("[A] [B] [C]\n 1   2\n\nmove 1 from 1 to 2");
+        assert_eq!((err.line, err.column), (1, 9));
+        assert_eq!(err.kind, ParseErrorKind::RowWiderThanFooter { num_stacks: 2 });
+    }
+
+    #[test]
+    fn test_misaligned_bracket() {
+        let err = parse_err("[A] [B]\n [C][D]\n 1   2\n\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 2, column: 5, kind: ParseErrorKind::MisalignedBracket });
+
+        let err = parse_err("[A] [B\n 1   2\n\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 1, column: 7, kind: ParseErrorKind::MisalignedBracket });
+
+        let err = parse_err("[A] []\n 1   2\n\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 1, column: 6, kind: ParseErrorKind::MisalignedBracket });
+
+        let err = parse_err("[ABCDEF]\n 1   2\n\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 1, column: 1, kind: ParseErrorKind::MisalignedBracket });
+
+        let err = parse_err("[A] x\n 1   2\n\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 1, column: 5, kind: ParseErrorKind::MisalignedBracket });
+    }
+
+    #[test]
+    fn test_variable_column_widths() {
+        let content = "     [LONG]\n[AB] [C]    [7]\n 1    2      3\n\nmove 1 from 2 to 3";
+        let (crates, moves) = parse_input(content).unwrap();
+        assert_eq!(crates.state, vec![vec!["AB"], vec!["C", "LONG"], vec!["7"]]);
+        assert_eq!(moves, vec![Move { num: 1, from: 2, to: 3 }]);
+    }
+
+    #[test]
+    fn test_missing_separator() {
+        let err = parse_err("[A] [B]\n 1   2\nmove 1 from 1 to 2");
+        assert_eq!(err, ParseError { line: 3, column: 1, kind: ParseErrorKind::MissingSeparator });
+    }
+
+    #[test]
+    fn test_non_numeric_count() {
+        let err = parse_err("[A] [B]\n 1   2\n\nmove 1 from 1 to 2\nmove two from 1 to 2");
+        assert_eq!((err.line, err.column), (5, 6));
+        assert_eq!(err.kind, ParseErrorKind::NonNumeric { field: "count", found: "two".to_string() });
+    }
+
+    #[test]
+    fn test_stack_out_of_range() {
+        let err = parse_err("[A] [B]\n 1   2\n\nmove 1 from 0 to 2");
+        assert_eq!((err.line, err.column), (4, 13));
+        assert_eq!(err.kind, ParseErrorKind::StackOutOfRange { index: 0, num_stacks: 2 });
+
+        let err = parse_err("[A] [B]\n 1   2\n\nmove 1 from 1 to 3");
+        assert_eq!((err.line, err.column), (4, 18));
+        assert_eq!(err.kind, ParseErrorKind::StackOutOfRange { index: 3, num_stacks: 2 });
+    }
+
+    #[test]
+    fn test_malformed_move() {
+        let err = parse_err("[A] [B]\n 1   2\n\nmove 1 from 1 into 2");
+        assert_eq!(err, ParseError { line: 4, column: 15, kind: ParseErrorKind::MalformedMove });
+
+        let err = parse_err("[A] [B]\n 1   2\n\nmove 1 from 1");
+        assert_eq!(err, ParseError { line: 4, column: 14, kind: ParseErrorKind::MalformedMove });
+    }
+
+    #[test]
+    fn test_read_input_errors() {
+        let err = read_input("../inputs/d05_missing").unwrap_err();
+        assert!(matches!(&err, InputError::Io(err) if err.kind() == io::ErrorKind::NotFound));
+
+        assert!(matches!(read_input("../inputs/d05_test"), Ok((_, moves)) if moves.len() == 4));
+        let err = read_input("../inputs/d09_test").unwrap_err();
+        assert!(matches!(err, InputError::Parse(ParseError { kind: ParseErrorKind::MissingFooter, .. })));
+    }
+
+    #[test]
+    fn test_error_message() {
+        let err = parse_err("[A] [B]\n 1   2\n\nmove 1 from 1 to 3");
+        assert_eq!(err.to_string(), "line 4, column 18: stack 3 is out of range 1..=2");
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+
+use crate::crane::Crane;
+use crate::{Crates, Move};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The two drawings don't have the same number of stacks.
+    StackCountMismatch { start: usize, target: usize },
+    /// The target holds a different multiset of crates, so no move list can reach it.
+    DifferentCrates,
+    /// Every state reachable with this crane was explored without finding the target.
+    Unreachable { explored: usize },
+    /// The search gave up after visiting `max_states` states.
+    LimitReached { max_states: usize },
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::StackCountMismatch { start, target } => {
+                write!(f, "start has {start} stacks, but target has {target}")
+            }
+            SolveError::DifferentCrates => write!(f, "start and target don't hold the same crates"),
+            SolveError::Unreachable { explored } => {
+                write!(f, "target is unreachable with this crane ({explored} states explored)")
+            }
+            SolveError::LimitReached { max_states } => write!(f, "gave up after exploring {max_states} states"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// A visited search state, with the index of its parent node and the move that led here.
+struct Node<C> {
+    crates: Crates,
+    crane: C,
+    parent: Option<(usize, Move)>,
+}
+
+fn sorted_crates(crates: &Crates) -> Vec<String> {
+    let mut all = crates.state.concat();
+    all.sort_unstable();
+    all
+}
+
+/// Finds a shortest move list that turns `start` into `target` with `crane`, by breadth-first search over stack
+/// states.
+///
+/// The crane's own state is part of the search state, so stateful cranes are handled too. The search fails with
+/// [`SolveError::LimitReached`] once it has seen `max_states` distinct states.
+pub fn solve<C: Crane + Clone + Eq + Hash>(
+    start: &Crates,
+    target: &Crates,
+    crane: C,
+    max_states: usize,
+) -> Result<Vec<Move>, SolveError> {
+    let num_stacks = start.state.len();
+    if num_stacks != target.state.len() {
+        return Err(SolveError::StackCountMismatch { start: num_stacks, target: target.state.len() });
+    }
+    if sorted_crates(start) != sorted_crates(target) {
+        return Err(SolveError::DifferentCrates);
+    }
+
+    let mut nodes = vec![Node { crates: start.clone(), crane: crane.clone(), parent: None }];
+    let mut seen = HashSet::from([(start.clone(), crane)]);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(node_idx) = queue.pop_front() {
+        let Node { crates, crane, .. } = &nodes[node_idx];
+        if crates == target {
+            return Ok(path_to(&nodes, node_idx));
+        }
+
+        let mut next_nodes = vec![];
+        for from in 1..=num_stacks {
+            for to in 1..=num_stacks {
+                for num in 1..=crates.state[from - 1].len() {
+                    let mv = Move { from, to, num };
+                    let mut next = crates.clone();
+                    let mut next_crane = crane.clone();
+                    next.apply(mv, &mut next_crane).unwrap();
+
+                    let key = (next, next_crane);
+                    if !seen.contains(&key) {
+                        if seen.len() == max_states {
+                            return Err(SolveError::LimitReached { max_states });
+                        }
+                        seen.insert(key.clone());
+                        let (crates, crane) = key;
+                        next_nodes.push(Node { crates, crane, parent: Some((node_idx, mv)) });
+                    }
+                }
+            }
+        }
+
+        for node in next_nodes {
+            queue.push_back(nodes.len());
+            nodes.push(node);
+        }
+    }
+
+    Err(SolveError::Unreachable { explored: nodes.len() })
+}
+
+fn path_to<C>(nodes: &[Node<C>], mut node_idx: usize) -> Vec<Move> {
+    let mut moves = vec![];
+    while let Some((parent_idx, mv)) = nodes[node_idx].parent {
+        moves.push(mv);
+        node_idx = parent_idx;
+    }
+    moves.reverse();
+    moves
+}
+
+/// Formats moves one per line, in the same `move N from A to B` syntax the input file uses.
+pub fn format_moves(moves: &[Move]) -> String {
+    moves.iter().map(|mv| format!("{mv}\n")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    use crate::crane::{AlternatingCrane, CrateMover9000, CrateMover9001, LimitedCrane};
+    use crate::parse::{parse_input, read_input};
+
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    fn crates(state: &[&str]) -> Crates {
+        Crates { state: state.iter().map(|stack| stack.chars().map(String::from).collect()).collect() }
+    }
+
+    fn check<C: Crane + Clone + Eq + Hash>(start: &Crates, target: &Crates, crane: C) -> Vec<Move> {
+        let moves = solve(start, target, crane.clone(), 100_000).unwrap();
+        let mut end = start.clone();
+        end.apply_all(&moves, &mut crane.clone()).unwrap();
+        assert_eq!(&end, target);
+        moves
+    }
+
+    #[test]
+    fn test_shortest_moves() {
+        let start = crates(&["AB", ""]);
+        let target = crates(&["", "BA"]);
+        assert_eq!(check(&start, &target, CrateMover9000), vec![Move { num: 2, from: 1, to: 2 }]);
+        assert_eq!(check(&start, &target, CrateMover9001).len(), 2);
+        assert_eq!(check(&start, &start, CrateMover9001), vec![]);
+
+        // Reversing a stack takes a second stack to reverse it onto, even for the CrateMover 9000.
+        assert_eq!(
+            check(&crates(&["ABC", ""]), &crates(&["", "CBA"]), CrateMover9000),
+            vec![Move { num: 3, from: 1, to: 2 }]
+        );
+        assert_eq!(
+            check(&crates(&["AB", "", ""]), &crates(&["", "", "AB"]), LimitedCrane { max_lift: NonZeroUsize::MIN })
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_solves_puzzle_example() {
+        let (start, moves) = read_input(TEST_PATH).unwrap();
+        for num_moves in [0, 2] {
+            let mut target = start.clone();
+            target.apply_all(&moves[..num_moves], &mut AlternatingCrane::default()).unwrap();
+            assert!(check(&start, &target, AlternatingCrane::default()).len() <= num_moves);
+        }
+
+        let mut target = start.clone();
+        target.apply_all(&moves, &mut CrateMover9000).unwrap();
+        assert!(check(&start, &target, CrateMover9000).len() <= moves.len());
+    }
+
+    #[test]
+    fn test_unreachable_targets() {
+        let start = crates(&["AB", ""]);
+        assert_eq!(solve(&start, &crates(&["AC", ""]), CrateMover9001, 1000), Err(SolveError::DifferentCrates));
+        assert_eq!(
+            solve(&start, &crates(&["AB"]), CrateMover9001, 1000),
+            Err(SolveError::StackCountMismatch { start: 2, target: 1 })
+        );
+        assert_eq!(
+            solve(&crates(&["AB"]), &crates(&["BA"]), CrateMover9001, 1000),
+            Err(SolveError::Unreachable { explored: 1 })
+        );
+        assert_eq!(
+            solve(&crates(&["ABC"]), &crates(&["CBA"]), CrateMover9000, 1000),
+            Err(SolveError::Unreachable { explored: 1 })
+        );
+        assert_eq!(
+            solve(&crates(&["ABCDEF", "", ""]), &crates(&["", "", "FEDCBA"]), CrateMover9001, 10),
+            Err(SolveError::LimitReached { max_states: 10 })
+        );
+    }
+
+    #[test]
+    fn test_format_moves_parses_back() {
+        let start = crates(&["AB", ""]);
+        let moves = solve(&start, &crates(&["", "BA"]), CrateMover9001, 1000).unwrap();
+        let content = format!("{start}\n\n{}", format_moves(&moves));
+        assert_eq!(parse_input(&content).unwrap(), (start, moves));
+    }
+}
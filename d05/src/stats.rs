@@ -0,0 +1,186 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::crane::Crane;
+use crate::{Crates, Move, MoveError};
+
+/// How one stack fared over a simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StackStats {
+    /// The lowest and highest the stack got, counting its initial height.
+    pub min_height: usize,
+    pub max_height: usize,
+    /// How many moves took crates from or put crates onto this stack.
+    pub times_source: usize,
+    pub times_destination: usize,
+    /// The 0-based index of the first move that left the stack empty after it had held crates.
+    pub first_emptied: Option<usize>,
+}
+
+/// Per-stack statistics and crane effort, collected move by move as the simulation runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Stats {
+    pub stacks: Vec<StackStats>,
+    pub moves: usize,
+    /// The total number of trips the crane made.
+    pub lifts: usize,
+}
+
+impl Stats {
+    pub fn new(crates: &Crates) -> Stats {
+        let stacks = crates
+            .state
+            .iter()
+            .map(|stack| StackStats {
+                min_height: stack.len(),
+                max_height: stack.len(),
+                times_source: 0,
+                times_destination: 0,
+                first_emptied: None,
+            })
+            .collect();
+
+        Stats { stacks, moves: 0, lifts: 0 }
+    }
+
+    /// Records move `move_idx`, given the state right after it and the number of trips it took.
+    pub fn record(&mut self, move_idx: usize, mv: Move, after: &Crates, lifts: usize) {
+        self.moves += 1;
+        self.lifts += lifts;
+        self.stacks[mv.from - 1].times_source += 1;
+        self.stacks[mv.to - 1].times_destination += 1;
+
+        for stack_idx in [mv.from - 1, mv.to - 1] {
+            let height = after.state[stack_idx].len();
+            let stats = &mut self.stacks[stack_idx];
+            stats.min_height = stats.min_height.min(height);
+            stats.max_height = stats.max_height.max(height);
+            if height == 0 && mv.num > 0 && stats.first_emptied.is_none() {
+                stats.first_emptied = Some(move_idx);
+            }
+        }
+    }
+}
+
+/// Prints the statistics as a table with one row per stack.
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "stack  min  max  source  dest  emptied")?;
+        for (stack_idx, stack) in self.stacks.iter().enumerate() {
+            let emptied = stack.first_emptied.map_or("-".to_string(), |move_idx| format!("#{move_idx}"));
+            writeln!(
+                f,
+                "{:>5}  {:>3}  {:>3}  {:>6}  {:>4}  {:>7}",
+                stack_idx + 1,
+                stack.min_height,
+                stack.max_height,
+                stack.times_source,
+                stack.times_destination,
+                emptied
+            )?;
+        }
+        write!(f, "{} lifts over {} moves", self.lifts, self.moves)
+    }
+}
+
+/// Runs `crane` over `moves` like [`Crates::apply_all`], collecting [`Stats`] along the way.
+///
+/// On error, returns the statistics up to the illegal move along with it.
+pub fn simulate_with_stats<C: Crane>(
+    crates: &Crates,
+    moves: &[Move],
+    crane: &mut C,
+) -> (Crates, Stats, Option<MoveError>) {
+    let mut crates = crates.clone();
+    let mut stats = Stats::new(&crates);
+    for (move_idx, &mv) in moves.iter().enumerate() {
+        let lifts = crane.lifts(mv.num);
+        if let Err(underflow) = crates.apply(mv, crane) {
+            return (crates, stats, Some(MoveError { move_idx, mv, underflow }));
+        }
+        stats.record(move_idx, mv, &crates, lifts);
+    }
+
+    (crates, stats, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroUsize;
+
+    use crate::crane::{CrateMover9000, CrateMover9001, LimitedCrane};
+    use crate::generate::{generate, PuzzleSpec, Rng};
+    use crate::parse::read_input;
+
+    const TEST_PATH: &str = "../inputs/d05_test";
+
+    #[test]
+    fn test_puzzle_example() {
+        let (crates, moves) = read_input(TEST_PATH).unwrap();
+        let (end, stats, err) = simulate_with_stats(&crates, &moves, &mut CrateMover9000);
+        assert_eq!(end.get_top_crates().into_iter().flatten().collect::<String>(), "CMZ");
+        assert!(err.is_none());
+
+        assert_eq!(
+            stats.stacks[0],
+            StackStats { min_height: 0, max_height: 3, times_source: 2, times_destination: 2, first_emptied: Some(1) }
+        );
+        assert_eq!(
+            stats.stacks[1],
+            StackStats { min_height: 0, max_height: 3, times_source: 2, times_destination: 1, first_emptied: Some(2) }
+        );
+        assert_eq!(
+            stats.stacks[2],
+            StackStats { min_height: 1, max_height: 4, times_source: 0, times_destination: 1, first_emptied: None }
+        );
+        assert_eq!((stats.moves, stats.lifts), (4, 7));
+
+        let (_, stats, _) = simulate_with_stats(&crates, &moves, &mut CrateMover9001);
+        assert_eq!(stats.lifts, 4);
+        let (_, stats, _) =
+            simulate_with_stats(&crates, &moves, &mut LimitedCrane { max_lift: NonZeroUsize::new(2).unwrap() });
+        assert_eq!(stats.lifts, 5);
+
+        assert_eq!(
+            stats.to_string(),
+            "stack  min  max  source  dest  emptied
+    1    0    3       2     2       #1
+    2    0    3       2     1       #2
+    3    1    4       0     1        -
+5 lifts over 4 moves"
+        );
+    }
+
+    #[test]
+    fn test_stops_at_illegal_move() {
+        let (crates, mut moves) = read_input(TEST_PATH).unwrap();
+        moves.insert(1, Move { num: 5, from: 3, to: 2 });
+
+        let (end, stats, err) = simulate_with_stats(&crates, &moves, &mut CrateMover9001);
+        assert_eq!(err.map(|err| err.move_idx), Some(1));
+        assert_eq!(stats.moves, 1);
+        assert_eq!(end.state[0], vec!["Z", "N", "D"]);
+    }
+
+    #[test]
+    fn test_heights_bound_every_step() {
+        let mut rng = Rng::new(13);
+        for _ in 0..50 {
+            let spec = PuzzleSpec { num_stacks: 5, max_height: 8, num_moves: 40, max_move: 6, max_label_len: 1 };
+            let (crates, moves) = generate(spec, &mut rng);
+            let (_, stats, _) = simulate_with_stats(&crates, &moves, &mut CrateMover9001);
+
+            let mut state = crates.clone();
+            for &mv in &moves {
+                state.apply(mv, &mut CrateMover9001).unwrap();
+                for (stack, stack_stats) in state.state.iter().zip(&stats.stacks) {
+                    assert!((stack_stats.min_height..=stack_stats.max_height).contains(&stack.len()));
+                }
+            }
+            let sources = stats.stacks.iter().map(|stack| stack.times_source).sum::<usize>();
+            assert_eq!(sources, moves.len());
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::Position;
+
+/// How the distance between two knots is measured.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// The largest offset along any axis, so diagonal neighbours are 1 apart, as in the puzzle.
+    #[default]
+    Chebyshev,
+    /// The sum of the offsets along every axis.
+    Manhattan,
+    /// The straight-line distance.
+    Euclidean,
+}
+
+impl Metric {
+    /// The distance between `a` and `b`, squared for [`Metric::Euclidean`] to stay in integers, so only good for
+    /// comparing against other measurements in the same metric.
+    fn measure<P: Position>(self, a: P, b: P) -> u64 {
+        let offsets = a.offsets(b).map(|offset| offset.unsigned_abs() as u64);
+        match self {
+            Metric::Chebyshev => offsets.max().unwrap_or(0),
+            Metric::Manhattan => offsets.sum(),
+            Metric::Euclidean => offsets.map(|offset| offset * offset).sum(),
+        }
+    }
+
+    /// Whether `a` and `b` are at most `slack` apart.
+    pub fn within<P: Position>(self, a: P, b: P, slack: u32) -> bool {
+        let slack = slack as u64;
+        match self {
+            Metric::Chebyshev | Metric::Manhattan => self.measure(a, b) <= slack,
+            Metric::Euclidean => self.measure(a, b) <= slack * slack,
+        }
+    }
+
+    /// Where a knot at `knot` moves once it is more than `slack` from `target`: the neighbouring position that brings
+    /// it back within slack with the shortest move in this metric, and of those the closest to `target`.
+    ///
+    /// With Chebyshev distance every neighbour is one move away, so the knot steps towards `target` on every axis,
+    /// as in the puzzle. With the other metrics a single-axis move is shorter than a diagonal one, so a knot that is
+    /// `(1, 1)` from its target with a slack of 1 moves along one axis only, rather than onto the target. Ties go to
+    /// the first in [`Position::neighbours`] order. If no neighbour is close enough, the knot steps towards `target`.
+    pub fn catch_up<P: Position>(self, knot: P, target: P, slack: u32) -> P {
+        let squared_distance = |position: P| Metric::Euclidean.measure(position, target);
+        knot.neighbours()
+            .filter(|&neighbour| self.within(neighbour, target, slack))
+            .min_by_key(|&neighbour| (self.measure(knot, neighbour), squared_distance(neighbour)))
+            .unwrap_or_else(|| knot.step_towards(target))
+    }
+}
+
+/// The slack of every segment of an elastic rope, and the metric it is measured in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elastic {
+    /// `slack[i]` is how far knot `i + 1` may be from knot `i` before it moves.
+    pub slack: Vec<u32>,
+    pub metric: Metric,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rope::Rope;
+    use crate::space::{Coords3, Direction3};
+    use crate::{parse_input, Coords, Direction, Instruction};
+
+    fn tail_visits(instructions: &[Instruction], slack: &[u32], metric: Metric) -> usize {
+        let tail_idx = slack.len();
+        let mut rope: Rope = Rope::tracking(slack.len() + 1, &[tail_idx]).with_slack(slack, metric);
+        rope.run(instructions).unwrap();
+        rope.visited(tail_idx).unwrap().len()
+    }
+
+    #[test]
+    fn test_within() {
+        let zero = Coords::default();
+        let diagonal = Coords { x: 1, y: 1 };
+        assert!(Metric::Chebyshev.within(zero, diagonal, 1));
+        assert!(!Metric::Manhattan.within(zero, diagonal, 1));
+        assert!(Metric::Manhattan.within(zero, diagonal, 2));
+        assert!(!Metric::Euclidean.within(zero, diagonal, 1));
+        assert!(Metric::Euclidean.within(zero, diagonal, 2));
+
+        let knight = Coords { x: 3, y: 4 };
+        assert!(Metric::Euclidean.within(zero, knight, 5));
+        assert!(!Metric::Euclidean.within(zero, knight, 4));
+        assert!(!Metric::Chebyshev.within(zero, knight, 3));
+        assert!(Metric::Chebyshev.within(knight, zero, 4));
+
+        let corner = Coords3 { x: 1, y: -1, z: 1 };
+        assert!(Metric::Chebyshev.within(Coords3::default(), corner, 1));
+        assert!(!Metric::Manhattan.within(Coords3::default(), corner, 2));
+    }
+
+    #[test]
+    fn test_catch_up_takes_the_shortest_step() {
+        let (zero, diagonal) = (Coords::default(), Coords { x: 1, y: 1 });
+        // One axis step is enough, so the knot doesn't land on its target.
+        assert_eq!(Metric::Manhattan.catch_up(zero, diagonal, 1), Coords { x: 1, y: 0 });
+        assert_eq!(Metric::Euclidean.catch_up(zero, diagonal, 1), Coords { x: 1, y: 0 });
+        assert_eq!(Metric::Manhattan.catch_up(zero, diagonal, 0), diagonal);
+
+        // Chebyshev steps cost the same either way, so the knot goes diagonally, as in the puzzle.
+        let knight = Coords { x: 2, y: 1 };
+        assert_eq!(Metric::Chebyshev.catch_up(zero, knight, 1), Coords { x: 1, y: 1 });
+        assert_eq!(Metric::Manhattan.catch_up(zero, knight, 2), Coords { x: 1, y: 0 });
+
+        let mut rope: Rope = Rope::new(2).with_slack(&[1], Metric::Manhattan);
+        rope.apply(Instruction { direction: Direction::UpRight, steps: 1 }).unwrap();
+        assert_eq!(rope.knots(), [diagonal, Coords { x: 1, y: 0 }]);
+    }
+
+    #[test]
+    fn test_unit_chebyshev_slack_reproduces_answers() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        assert_eq!(tail_visits(&instructions, &[1], Metric::Chebyshev), 6026);
+        assert_eq!(tail_visits(&instructions, &[1; 9], Metric::Chebyshev), 2273);
+
+        let mut elastic: Rope = Rope::new(10).with_slack(&[1; 9], Metric::Chebyshev);
+        elastic.run(&instructions).unwrap();
+        let mut rigid: Rope = Rope::new(10);
+        rigid.run(&instructions).unwrap();
+        assert_eq!(elastic.knots(), rigid.knots());
+        for knot_idx in 0..10 {
+            assert_eq!(elastic.visited(knot_idx), rigid.visited(knot_idx));
+        }
+    }
+
+    #[test]
+    fn test_more_slack_means_less_movement() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        for metric in [Metric::Chebyshev, Metric::Manhattan, Metric::Euclidean] {
+            let counts = (1..=4).map(|slack| tail_visits(&instructions, &[slack; 9], metric)).collect::<Vec<_>>();
+            assert!(counts.windows(2).all(|pair| pair[0] > pair[1]), "{metric:?}: {counts:?}");
+        }
+
+        // Manhattan and Euclidean distances are never shorter than Chebyshev, so their tails move more.
+        assert!(tail_visits(&instructions, &[1; 9], Metric::Manhattan) > 2273);
+        assert!(tail_visits(&instructions, &[1; 9], Metric::Euclidean) > 2273);
+    }
+
+    #[test]
+    fn test_per_segment_slack() {
+        let mut rope: Rope = Rope::new(3).with_slack(&[3, 1], Metric::Chebyshev);
+        rope.apply(Instruction { direction: Direction::Right, steps: 3 }).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 3, y: 0 }, Coords::default(), Coords::default()]);
+
+        rope.apply(Instruction { direction: Direction::Right, steps: 2 }).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 5, y: 0 }, Coords { x: 2, y: 0 }, Coords { x: 1, y: 0 }]);
+
+        // A slack of 0 pins a knot onto the one before it.
+        let mut rope: Rope<Coords3> = Rope::new(2).with_slack(&[0], Metric::Manhattan);
+        rope.apply(Instruction { direction: Direction3::Forward, steps: 4 }).unwrap();
+        assert_eq!(rope.head(), rope.tail());
+        assert_eq!(rope.visited(1).unwrap().len(), 5);
+    }
+}
@@ -0,0 +1,331 @@
+pub mod elastic;
+pub mod lockstep;
+pub mod obstacles;
+pub mod render;
+pub mod rope;
+pub mod space;
+pub mod stats;
+pub mod stream;
+pub mod visited;
+
+use std::hash::Hash;
+use std::{fmt, fs, io};
+
+use rope::Rope;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+/// Moves the head `steps` times in `direction`; `D` is [`Direction`] for the plane or
+/// [`Direction3`](space::Direction3) for space.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Instruction<D = Direction> {
+    pub direction: D,
+    /// At most [`MAX_STEPS`], so that a single move never overflows a coordinate on its own.
+    pub steps: u32,
+}
+
+/// The most steps one instruction may take: the furthest a coordinate can move from the origin.
+pub const MAX_STEPS: u32 = i32::MAX as u32;
+
+/// A knot would have moved past the largest or smallest value a coordinate can hold.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OutOfBounds;
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the rope moves further than a coordinate can hold")
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+/// A knot position, in whatever space the rope lives in.
+pub trait Position: Copy + Default + Eq + Hash {
+    type Direction: Copy;
+
+    /// Whether the two positions touch, diagonals and overlap included.
+    fn is_adjacent(self, another: Self) -> bool;
+
+    /// The next position in `direction`, or `None` if a coordinate can't hold it.
+    fn move_in_direction(self, direction: Self::Direction) -> Option<Self>;
+
+    /// Moves `steps` times in `direction`, or returns `None` if a coordinate can't hold the result.
+    fn move_by(self, direction: Self::Direction, steps: u32) -> Option<Self> {
+        (0..steps).try_fold(self, |position, _| position.move_in_direction(direction))
+    }
+
+    /// Where a knot at `self` moves to catch up with a `target` it no longer touches.
+    fn follow(self, target: Self) -> Self;
+
+    /// Moves one unit towards `target` on every axis where they differ.
+    fn step_towards(self, target: Self) -> Self;
+
+    /// The offset from `other` to `self` along every axis.
+    fn offsets(self, other: Self) -> impl Iterator<Item = i32>;
+
+    /// Every position touching this one, not counting itself or any a coordinate can't hold.
+    fn neighbours(self) -> impl Iterator<Item = Self>;
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Coords {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position for Coords {
+    type Direction = Direction;
+
+    fn is_adjacent(self, another: Coords) -> bool {
+        (self == another) || (((self.x - another.x).abs() <= 1) && ((self.y - another.y).abs() <= 1))
+    }
+
+    fn move_in_direction(self, direction: Direction) -> Option<Coords> {
+        self.move_by(direction, 1)
+    }
+
+    fn move_by(self, direction: Direction, steps: u32) -> Option<Coords> {
+        let (dx, dy) = match direction {
+            Direction::Right => (1, 0),
+            Direction::Left => (-1, 0),
+            Direction::Up => (0, 1),
+            Direction::Down => (0, -1),
+            Direction::UpLeft => (-1, 1),
+            Direction::UpRight => (1, 1),
+            Direction::DownLeft => (-1, -1),
+            Direction::DownRight => (1, -1),
+        };
+        let steps = i32::try_from(steps).ok()?;
+        Some(Coords { x: self.x.checked_add(dx * steps)?, y: self.y.checked_add(dy * steps)? })
+    }
+
+    fn follow(self, target: Coords) -> Coords {
+        let possible_new_x = if (self.x - target.x).abs() >= 1 {
+            if target.x > self.x {
+                self.x + 1
+            } else {
+                self.x - 1
+            }
+        } else {
+            self.x
+        };
+
+        let possible_new_y = if (self.y - target.y).abs() >= 1 {
+            if target.y > self.y {
+                self.y + 1
+            } else {
+                self.y - 1
+            }
+        } else {
+            self.y
+        };
+
+        if possible_new_x != self.x && possible_new_y != self.y {
+            Coords { x: possible_new_x, y: possible_new_y }
+        } else if target.is_adjacent(Coords { x: possible_new_x, y: self.y }) {
+            Coords { x: possible_new_x, y: self.y }
+        } else if target.is_adjacent(Coords { x: self.x, y: possible_new_y }) {
+            Coords { x: self.x, y: possible_new_y }
+        } else {
+            Coords { x: possible_new_x, y: possible_new_y }
+        }
+    }
+
+    fn step_towards(self, target: Coords) -> Coords {
+        Coords { x: self.x + (target.x - self.x).signum(), y: self.y + (target.y - self.y).signum() }
+    }
+
+    fn offsets(self, other: Coords) -> impl Iterator<Item = i32> {
+        [self.x - other.x, self.y - other.y].into_iter()
+    }
+
+    fn neighbours(self) -> impl Iterator<Item = Coords> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .filter_map(move |(dx, dy)| Some(Coords { x: self.x.checked_add(dx)?, y: self.y.checked_add(dy)? }))
+    }
+}
+
+pub fn parse_input(path: &str) -> io::Result<Vec<Instruction>> {
+    parse_instructions(path, parse_direction)
+}
+
+pub fn parse_direction(direction: &str) -> Option<Direction> {
+    match direction {
+        "L" => Some(Direction::Left),
+        "R" => Some(Direction::Right),
+        "U" => Some(Direction::Up),
+        "D" => Some(Direction::Down),
+        "UL" => Some(Direction::UpLeft),
+        "UR" => Some(Direction::UpRight),
+        "DL" => Some(Direction::DownLeft),
+        "DR" => Some(Direction::DownRight),
+        _ => None,
+    }
+}
+
+/// Parses a `DIRECTION STEPS` line, or returns `None` if it is malformed or takes more than [`MAX_STEPS`] steps.
+pub fn parse_instruction<D>(line: &str, parse_direction: impl Fn(&str) -> Option<D>) -> Option<Instruction<D>> {
+    let mut items = line.split_ascii_whitespace();
+    let direction = parse_direction(items.next()?)?;
+    let steps = items.next()?.parse().ok().filter(|&steps| steps <= MAX_STEPS)?;
+    match items.next() {
+        Some(_) => None,
+        None => Some(Instruction { direction, steps }),
+    }
+}
+
+/// The error for a malformed instruction on a 1-based line.
+pub(crate) fn incorrect_input(line_num: usize, line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {line_num}: incorrect input {:?}", line.trim_end()))
+}
+
+/// Reads one instruction per line, skipping blank lines; a malformed line gives an [`io::ErrorKind::InvalidData`]
+/// error naming its 1-based line number.
+fn parse_instructions<D>(path: &str, parse_direction: impl Fn(&str) -> Option<D>) -> io::Result<Vec<Instruction<D>>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_idx, line)| {
+            parse_instruction(line, &parse_direction).ok_or_else(|| incorrect_input(line_idx + 1, line))
+        })
+        .collect()
+}
+
+pub fn p1(instructions: &[Instruction]) -> Result<usize, OutOfBounds> {
+    tail_visits::<Coords>(instructions, 2)
+}
+
+pub fn p2(instructions: &[Instruction]) -> Result<usize, OutOfBounds> {
+    tail_visits::<Coords>(instructions, 10)
+}
+
+/// Counts the positions visited by the tail of a rope with `num_knots` knots.
+pub fn tail_visits<P: Position>(
+    instructions: &[Instruction<P::Direction>],
+    num_knots: usize,
+) -> Result<usize, OutOfBounds> {
+    let tail_idx = num_knots - 1;
+    let mut rope = Rope::<P>::tracking(num_knots, &[tail_idx]);
+    rope.run(instructions)?;
+    Ok(rope.visited(tail_idx).unwrap().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing() {
+        let test_instructions = parse_input("../inputs/d09_test").unwrap();
+        assert_eq!(test_instructions.len(), 8);
+        assert_eq!(test_instructions[0], Instruction { steps: 4, direction: Direction::Right });
+
+        let instructions = parse_input("../inputs/d09").unwrap();
+        assert_eq!(instructions.len(), 2000);
+
+        let diagonal_instructions = parse_input("../inputs/d09_test_diagonal").unwrap();
+        assert_eq!(diagonal_instructions.len(), 8);
+        assert_eq!(diagonal_instructions[0], Instruction { steps: 3, direction: Direction::UpRight });
+        assert_eq!(diagonal_instructions[2], Instruction { steps: 4, direction: Direction::DownLeft });
+
+        let longest = parse_instruction("L 2147483647", parse_direction);
+        assert_eq!(longest, Some(Instruction { steps: MAX_STEPS, direction: Direction::Left }));
+        assert_eq!(parse_instruction("L 2147483648", parse_direction), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = parse_input("../inputs/d09_missing").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+        // The crate puzzle's drawing is no list of moves.
+        let err = parse_input("../inputs/d05_test").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 1: incorrect input \"    [D]\"");
+    }
+
+    #[test]
+    fn test_is_adjacent() {
+        assert!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 1, y: 1 }));
+        assert!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 2, y: 1 }));
+        assert!(Coords { x: 1, y: 2 }.is_adjacent(Coords { x: 2, y: 1 }));
+        assert!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 1, y: 1 }));
+
+        assert!(!Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 1, y: 3 }));
+    }
+
+    #[test]
+    fn test_move_in_direction() {
+        let zero = Coords::default();
+        assert_eq!(zero.move_in_direction(Direction::Up), Some(Coords { x: 0, y: 1 }));
+        assert_eq!(zero.move_in_direction(Direction::Down), Some(Coords { x: 0, y: -1 }));
+        assert_eq!(zero.move_in_direction(Direction::Left), Some(Coords { x: -1, y: 0 }));
+        assert_eq!(zero.move_in_direction(Direction::Right), Some(Coords { x: 1, y: 0 }));
+        assert_eq!(zero.move_in_direction(Direction::UpLeft), Some(Coords { x: -1, y: 1 }));
+        assert_eq!(zero.move_in_direction(Direction::UpRight), Some(Coords { x: 1, y: 1 }));
+        assert_eq!(zero.move_in_direction(Direction::DownLeft), Some(Coords { x: -1, y: -1 }));
+        assert_eq!(zero.move_in_direction(Direction::DownRight), Some(Coords { x: 1, y: -1 }));
+
+        let edge = Coords { x: i32::MAX, y: i32::MIN };
+        assert_eq!(edge.move_in_direction(Direction::Right), None);
+        assert_eq!(edge.move_in_direction(Direction::Down), None);
+        assert_eq!(edge.move_by(Direction::UpLeft, MAX_STEPS), Some(Coords { x: 0, y: -1 }));
+        assert_eq!(edge.neighbours().count(), 3);
+    }
+
+    #[test]
+    fn test_move_tail() {
+        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 2, y: 3 }), Coords { x: 2, y: 2 });
+        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 3, y: 2 }), Coords { x: 2, y: 2 });
+
+        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 3, y: 1 }), Coords { x: 2, y: 1 });
+        assert_eq!(Coords { x: 1, y: 3 }.follow(Coords { x: 1, y: 1 }), Coords { x: 1, y: 2 });
+
+        assert_eq!(Coords { x: 1, y: 0 }.follow(Coords { x: 3, y: 0 }), Coords { x: 2, y: 0 });
+
+        // A diagonal head move can leave the head two steps away on both axes.
+        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 3, y: 3 }), Coords { x: 2, y: 2 });
+        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: -1, y: 3 }), Coords { x: 0, y: 2 });
+    }
+
+    #[test]
+    fn test_p1() {
+        let test_instructions = parse_input("../inputs/d09_test").unwrap();
+        assert_eq!(p1(&test_instructions), Ok(13));
+
+        let instructions = parse_input("../inputs/d09").unwrap();
+        assert_eq!(p1(&instructions), Ok(6026));
+
+        // Each move fits in a coordinate on its own, but together they go past the edge.
+        let too_far = [
+            Instruction { direction: Direction::Right, steps: 10 },
+            Instruction { direction: Direction::Right, steps: MAX_STEPS },
+        ];
+        assert_eq!(p1(&too_far), Err(OutOfBounds));
+    }
+
+    #[test]
+    fn test_p2() {
+        let test_instructions = parse_input("../inputs/d09_test").unwrap();
+        assert_eq!(p2(&test_instructions), Ok(1));
+
+        let test_instructions2 = parse_input("../inputs/d09_test2").unwrap();
+        assert_eq!(p2(&test_instructions2), Ok(36));
+
+        let instructions = parse_input("../inputs/d09").unwrap();
+        assert_eq!(p2(&instructions), Ok(2273));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use crate::rope::{Rope, Tick};
+use crate::{Coords, Instruction, OutOfBounds};
+
+/// One of several ropes sharing the plane: where all its knots start, and how its head moves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RopeInput {
+    pub start: Coords,
+    pub instructions: Vec<Instruction>,
+}
+
+/// Knots from different ropes on the same cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    /// How many ticks had passed: 0 for ropes that start on the same cell, 1 after the first head step, and so on.
+    pub tick: usize,
+    pub cell: Coords,
+    /// The indices of the ropes with a knot there, in increasing order.
+    pub ropes: Vec<usize>,
+}
+
+/// What happened when several ropes moved together.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockstep {
+    /// The number of ticks until the last rope ran out of instructions.
+    pub ticks: usize,
+    /// How many positions each rope's tail visited.
+    pub visited: Vec<usize>,
+    /// Every collision, by tick and then by cell from the top left, the way the plane is drawn.
+    pub collisions: Vec<Collision>,
+}
+
+/// Moves every rope's head one step per tick, each following its own instructions, until all of them are done.
+///
+/// A rope that runs out of instructions stays where it is and can still be collided with. Knots of the same rope
+/// overlapping is not a collision. Returns [`OutOfBounds`] if any knot would move past what a coordinate can hold.
+pub fn simulate_ropes(ropes: &[RopeInput], num_knots: usize) -> Result<Lockstep, OutOfBounds> {
+    let tail_idx = num_knots - 1;
+    let mut states = ropes.iter().map(|_| Rope::tracking(num_knots, &[tail_idx])).collect::<Vec<Rope>>();
+    let mut positions = ropes.iter().map(|rope| vec![rope.start; num_knots]).collect::<Vec<_>>();
+    let mut collisions = find_collisions(0, &positions);
+
+    let mut simulations =
+        states.iter_mut().zip(ropes).map(|(state, rope)| state.simulate(&rope.instructions)).collect::<Vec<_>>();
+    let mut ticks = 0;
+    loop {
+        let mut moved = false;
+        for ((simulation, knots), rope) in simulations.iter_mut().zip(&mut positions).zip(ropes) {
+            if let Some(tick) = simulation.next() {
+                let Tick { knots: new_knots, .. } = tick?;
+                // Every rope runs from the origin; shift it onto its own start.
+                *knots = new_knots
+                    .iter()
+                    .map(|knot| {
+                        let x = knot.x.checked_add(rope.start.x).ok_or(OutOfBounds)?;
+                        Ok(Coords { x, y: knot.y.checked_add(rope.start.y).ok_or(OutOfBounds)? })
+                    })
+                    .collect::<Result<_, _>>()?;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+        ticks += 1;
+        collisions.extend(find_collisions(ticks, &positions));
+    }
+
+    let visited = states.iter().map(|state| state.visited(tail_idx).unwrap().len()).collect();
+    Ok(Lockstep { ticks, visited, collisions })
+}
+
+fn find_collisions(tick: usize, positions: &[Vec<Coords>]) -> Vec<Collision> {
+    let mut occupants: HashMap<Coords, Vec<usize>> = HashMap::new();
+    for (rope_idx, knots) in positions.iter().enumerate() {
+        for &knot in knots {
+            let ropes = occupants.entry(knot).or_default();
+            if ropes.last() != Some(&rope_idx) {
+                ropes.push(rope_idx);
+            }
+        }
+    }
+
+    let mut collisions = occupants
+        .into_iter()
+        .filter(|(_, ropes)| ropes.len() > 1)
+        .map(|(cell, ropes)| Collision { tick, cell, ropes })
+        .collect::<Vec<_>>();
+    collisions.sort_by_key(|collision| (-collision.cell.y, collision.cell.x));
+    collisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use crate::{p2, parse_input, Direction};
+
+    fn collision(tick: usize, (x, y): (i32, i32), ropes: &[usize]) -> Collision {
+        Collision { tick, cell: Coords { x, y }, ropes: ropes.to_vec() }
+    }
+
+    #[test]
+    fn test_head_on() {
+        // Two ropes pass through each other, past a third that never moves.
+        let ropes = [
+            RopeInput {
+                start: Coords::default(),
+                instructions: vec![Instruction { direction: Direction::Right, steps: 4 }],
+            },
+            RopeInput {
+                start: Coords { x: 5, y: 0 },
+                instructions: vec![Instruction { direction: Direction::Left, steps: 4 }],
+            },
+            RopeInput { start: Coords { x: 2, y: 0 }, instructions: vec![] },
+        ];
+        let lockstep = simulate_ropes(&ropes, 2).unwrap();
+        assert_eq!(lockstep.ticks, 4);
+        assert_eq!(lockstep.visited, [4, 4, 1]);
+        let expected = [
+            collision(2, (2, 0), &[0, 2]),
+            collision(3, (2, 0), &[0, 1, 2]),
+            collision(3, (3, 0), &[0, 1]),
+            collision(4, (2, 0), &[1, 2]),
+        ];
+        assert_eq!(lockstep.collisions, expected);
+    }
+
+    #[test]
+    fn test_overlapping_starts() {
+        // Ropes that start on the same cell collide before anything moves, even with no instructions at all.
+        let still = |x, y| RopeInput { start: Coords { x, y }, instructions: vec![] };
+        let lockstep = simulate_ropes(&[still(1, 1), still(0, 0), still(1, 1)], 3).unwrap();
+        assert_eq!(lockstep.ticks, 0);
+        assert_eq!(lockstep.visited, [1, 1, 1]);
+        assert_eq!(lockstep.collisions, [collision(0, (1, 1), &[0, 2])]);
+
+        // One rope leaving the other's start ends the collision after the first tick.
+        let leaving = RopeInput {
+            start: Coords { x: 1, y: 1 },
+            instructions: vec![Instruction { direction: Direction::Up, steps: 2 }],
+        };
+        let lockstep = simulate_ropes(&[still(1, 1), leaving], 2).unwrap();
+        assert_eq!(lockstep.ticks, 2);
+        assert_eq!(lockstep.collisions, [collision(0, (1, 1), &[0, 1]), collision(1, (1, 1), &[0, 1])]);
+    }
+
+    #[test]
+    fn test_ropes_move_independently() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        let test_instructions = parse_input("../inputs/d09_test").unwrap();
+        let ropes = [
+            RopeInput { start: Coords { x: -1000, y: 1000 }, instructions: instructions.clone() },
+            RopeInput { start: Coords { x: 1000, y: -1000 }, instructions: test_instructions.clone() },
+        ];
+        let lockstep = simulate_ropes(&ropes, 10).unwrap();
+        assert_eq!(lockstep.visited, [p2(&instructions).unwrap(), p2(&test_instructions).unwrap()]);
+        assert_eq!(lockstep.ticks, instructions.iter().map(|instruction| instruction.steps as usize).sum::<usize>());
+
+        // Two copies of the same rope collide on every tick, on every cell the rope covers.
+        let copies = [ropes[1].clone(), ropes[1].clone()];
+        let lockstep = simulate_ropes(&copies, 10).unwrap();
+        assert_eq!(lockstep.visited, [1, 1]);
+        let ticks = lockstep.collisions.iter().map(|collision| collision.tick).collect::<HashSet<_>>();
+        assert_eq!(ticks, (0..=lockstep.ticks).collect());
+        assert!(lockstep.collisions.iter().all(|collision| collision.ropes == [0, 1]));
+    }
+
+    #[test]
+    fn test_shifted_rope_past_the_edge() {
+        let rope = RopeInput {
+            start: Coords { x: i32::MAX - 2, y: 0 },
+            instructions: vec![Instruction { direction: Direction::Right, steps: 3 }],
+        };
+        assert_eq!(simulate_ropes(&[rope], 2), Err(OutOfBounds));
+    }
+}
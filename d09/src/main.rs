@@ -1,211 +1,274 @@
 use std::collections::HashSet;
-use std::fs;
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::process;
+use std::time::Instant;
+
+use d09::elastic::Metric;
+use d09::lockstep::{self, RopeInput};
+use d09::obstacles;
+use d09::render::{self, Bounds};
+use d09::rope::Rope;
+use d09::stats::simulate_with_stats;
+use d09::stream::{self, Progress};
+use d09::visited::{TileSet, VisitedSet};
+use d09::{p1, p2, parse_input, Coords, Direction, Instruction, OutOfBounds};
+
+const INPUT_PATH: &str = "../inputs/d09";
+
+const USAGE: &str =
+    "usage: d09 [INPUT] [--slack K [--metric M]] [--draw] [--stats] [--heatmap PATH [--scale N] [--knot K]]
+           [--stream [--every N]] [--bench STEPS] [--walls MAP] [--rope PATH[@X,Y]]... [--knots N]
+
+  INPUT          the head's moves (default: ../inputs/d09, or stdin with --stream)
+  --slack        answer both parts with an elastic rope whose knots may be up to K apart (default: 1)
+  --metric       distance for the slack: chebyshev, manhattan or euclidean (default: chebyshev)
+  --draw         print the tail's visited positions and the final knot positions as a grid
+  --stats        print how often each knot moved, straight or diagonally, and where it spent the most steps
+  --heatmap      write a PPM heat map of how often a knot was at each position to PATH
+  --scale        pixels per position in the heat map (default: 4)
+  --knot         the knot to draw in the heat map, 0 being the head (default: the tail)
+  --stream       read the moves one line at a time and print the tail's running visited count
+  --every        print the running count every N instructions (default: 100000)
+  --bench        time the HashSet and tile-bitmap visited sets on a random walk of STEPS head steps
+  --walls        run the rope among the walls drawn in MAP (`#` wall, `.` free, `s` start) and list blocked moves
+  --rope         move another rope, starting at X,Y (default: 0,0), by the moves in PATH; with two or more, run
+                 them in lockstep and list the cells where knots of different ropes meet
+  --knots        number of knots in the rope to draw, stream, benchmark, run among walls or in lockstep (default: 10)";
+
+#[derive(Debug, Default)]
+struct Args {
+    input: Option<String>,
+    slack: Option<u32>,
+    metric: Option<Metric>,
+    draw: bool,
+    stats: bool,
+    heatmap: Option<String>,
+    scale: Option<usize>,
+    knot: Option<usize>,
+    knots: Option<usize>,
+    stream: bool,
+    every: Option<usize>,
+    bench: Option<u64>,
+    walls: Option<String>,
+    ropes: Vec<(String, Coords)>,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Instruction {
-    direction: Direction,
-    steps: u8,
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-struct Coords {
-    x: i32,
-    y: i32,
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args::default();
+    let mut it = env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--slack" => args.slack = Some(value()?.parse().map_err(|_| "--slack must be a number".to_string())?),
+            "--metric" => {
+                args.metric = Some(match value()?.as_str() {
+                    "chebyshev" => Metric::Chebyshev,
+                    "manhattan" => Metric::Manhattan,
+                    "euclidean" => Metric::Euclidean,
+                    other => return Err(format!("unknown metric {other:?}")),
+                })
+            }
+            "--draw" => args.draw = true,
+            "--stats" => args.stats = true,
+            "--heatmap" => args.heatmap = Some(value()?),
+            "--scale" => args.scale = Some(value()?.parse().map_err(|_| "--scale must be a number".to_string())?),
+            "--knot" => args.knot = Some(value()?.parse().map_err(|_| "--knot must be a number".to_string())?),
+            "--knots" => args.knots = Some(value()?.parse().map_err(|_| "--knots must be a number".to_string())?),
+            "--stream" => args.stream = true,
+            "--every" => args.every = Some(value()?.parse().map_err(|_| "--every must be a number".to_string())?),
+            "--bench" => args.bench = Some(value()?.parse().map_err(|_| "--bench must be a number".to_string())?),
+            "--walls" => args.walls = Some(value()?),
+            "--rope" => args.ropes.push(parse_rope(&value()?)?),
+            "-h" | "--help" => return Err(String::new()),
+            _ if !arg.starts_with('-') && args.input.is_none() => args.input = Some(arg),
+            _ => return Err(format!("unexpected argument {arg:?}")),
+        }
+    }
+    Ok(args)
 }
 
-impl Default for Coords {
-    fn default() -> Self {
-        Self { x: 0, y: 0 }
-    }
+/// Parses `PATH` or `PATH@X,Y` into the path and the start position.
+fn parse_rope(rope: &str) -> Result<(String, Coords), String> {
+    let Some((path, start)) = rope.rsplit_once('@') else {
+        return Ok((rope.to_string(), Coords::default()));
+    };
+    let start = start
+        .split_once(',')
+        .and_then(|(x, y)| Some(Coords { x: x.trim().parse().ok()?, y: y.trim().parse().ok()? }))
+        .ok_or_else(|| format!("--rope start must be X,Y, not {start:?}"))?;
+    Ok((path.to_string(), start))
 }
 
-impl Coords {
-    fn is_adjacent(self, another: Coords) -> bool {
-        (self == another) || (((self.x - another.x).abs() <= 1) && ((self.y - another.y).abs() <= 1))
-    }
+fn stream(args: &Args, num_knots: usize) -> Result<(), String> {
+    let every = args.every.unwrap_or(100_000);
+    let print = |Progress { instructions, visited }| println!("{instructions} instructions: {visited} visited");
 
-    fn move_in_direction(self, direction: Direction) -> Coords {
-        let (x, y) = match direction {
-            Direction::Right => (self.x + 1, self.y),
-            Direction::Left => (self.x - 1, self.y),
-            Direction::Up => (self.x, self.y + 1),
-            Direction::Down => (self.x, self.y - 1),
-        };
+    let progress = match &args.input {
+        Some(path) => {
+            let file = File::open(path).map_err(|err| format!("{path}: {err}"))?;
+            stream::stream(BufReader::new(file), num_knots, every, print).map_err(|err| format!("{path}: {err}"))?
+        }
+        None => stream::stream(io::stdin().lock(), num_knots, every, print).map_err(|err| format!("stdin: {err}"))?,
+    };
+    println!("Visited: {}.", progress.visited);
+    Ok(())
+}
 
-        Coords { x, y }
-    }
+fn elastic_tail_visits(
+    instructions: &[Instruction],
+    num_knots: usize,
+    slack: u32,
+    metric: Metric,
+) -> Result<usize, OutOfBounds> {
+    let mut rope: Rope = Rope::tracking(num_knots, &[num_knots - 1]).with_slack(&vec![slack; num_knots - 1], metric);
+    rope.run(instructions)?;
+    Ok(rope.visited(num_knots - 1).unwrap().len())
+}
 
-    fn follow(self, target: Coords) -> Coords {
-        let possible_new_x = if (self.x - target.x).abs() >= 1 {
-            if target.x > self.x {
-                self.x + 1
-            } else {
-                self.x - 1
-            }
-        } else {
-            self.x
-        };
-
-        let possible_new_y = if (self.y - target.y).abs() >= 1 {
-            if target.y > self.y {
-                self.y + 1
-            } else {
-                self.y - 1
-            }
-        } else {
-            self.y
-        };
-
-        if possible_new_x != self.x && possible_new_y != self.y {
-            Coords { x: possible_new_x, y: possible_new_y }
-        } else if target.is_adjacent(Coords { x: possible_new_x, y: self.y }) {
-            Coords { x: possible_new_x, y: self.y }
-        } else if target.is_adjacent(Coords { x: self.x, y: possible_new_y }) {
-            Coords { x: self.x, y: possible_new_y }
-        } else {
-            Coords { x: possible_new_x, y: possible_new_y }
+const BENCH_SEED: u64 = 9;
+
+/// A random walk of `num_steps` head steps in the four straight directions, split into instructions of 1 to 16
+/// steps. The same seed always gives the same walk.
+fn random_walk(num_steps: u64, seed: u64) -> impl Iterator<Item = Instruction> {
+    // Xorshift, which gets stuck at zero, so nudge that seed away from it.
+    let mut state = seed.max(1);
+    let mut remaining = num_steps;
+    std::iter::from_fn(move || {
+        if remaining == 0 {
+            return None;
         }
-    }
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+
+        let direction = [Direction::Left, Direction::Right, Direction::Up, Direction::Down][(state & 3) as usize];
+        let steps = (1 + (state >> 2) % 16).min(remaining);
+        remaining -= steps;
+        Some(Instruction { direction, steps: steps as u32 })
+    })
 }
 
-fn parse_input(path: &str) -> Vec<Instruction> {
-    let mut directions = vec![];
-    for line in fs::read_to_string(path).unwrap().trim_end().split("\n") {
-        let items = line.split_ascii_whitespace().collect::<Vec<_>>();
-        let direction = match items[0] {
-            "L" => Direction::Left,
-            "R" => Direction::Right,
-            "U" => Direction::Up,
-            "D" => Direction::Down,
-            _ => panic!("incorrect input"),
-        };
-        let steps: u8 = items[1].parse().unwrap();
-        directions.push(Instruction { direction, steps });
+fn bench<S: VisitedSet<Coords>>(name: &str, num_steps: u64, num_knots: usize) -> usize {
+    let now = Instant::now();
+    let mut rope: Rope<Coords, S> = Rope::tracking(num_knots, &[num_knots - 1]);
+    for instruction in random_walk(num_steps, BENCH_SEED) {
+        // A walk of up to 16 steps at a time is far from the edge of any coordinate.
+        rope.apply(instruction).expect("benchmark walks stay in range");
     }
-    directions
+    let visited = rope.visited(num_knots - 1).unwrap().len();
+    let duration = now.elapsed();
+    println!("{name}: {visited} visited [{duration:?}]");
+    visited
 }
 
-fn p1(instructions: &Vec<Instruction>) -> usize {
-    let mut head_pos = Coords::default();
-    let mut tail_pos = Coords::default();
-    let mut tail_visited: HashSet<Coords> = HashSet::new();
-    tail_visited.insert(tail_pos);
-
-    for &Instruction { direction, steps } in instructions {
-        for _ in 0..steps {
-            head_pos = head_pos.move_in_direction(direction);
-            if !head_pos.is_adjacent(tail_pos) {
-                tail_pos = tail_pos.follow(head_pos);
-                tail_visited.insert(tail_pos);
-            }
+fn run(args: Args) -> Result<(), String> {
+    let num_knots = args.knots.unwrap_or(10);
+    if num_knots == 0 {
+        return Err("--knots must be at least 1".to_string());
+    }
+    if args.stream {
+        return stream(&args, num_knots);
+    }
+    if let Some(num_steps) = args.bench {
+        let hashed = bench::<HashSet<Coords>>("HashSet", num_steps, num_knots);
+        let tiled = bench::<TileSet>("TileSet", num_steps, num_knots);
+        if hashed != tiled {
+            return Err(format!("visited sets disagree: {hashed} vs {tiled}"));
         }
+        return Ok(());
     }
 
-    tail_visited.len()
-}
-
-fn p2(instructions: &Vec<Instruction>) -> usize {
-    let mut positions = vec![Coords::default(); 10];
-    let mut tail_visited: HashSet<Coords> = HashSet::new();
-    tail_visited.insert(positions[9]);
-
-    for &Instruction { direction, steps } in instructions {
-        for _ in 0..steps {
-            positions[0] = positions[0].move_in_direction(direction);
-
-            for knot_idx in 1..10 {
-                if !positions[knot_idx - 1].is_adjacent(positions[knot_idx]) {
-                    positions[knot_idx] = positions[knot_idx].follow(positions[knot_idx - 1]);
-                    if knot_idx == 9 {
-                        tail_visited.insert(positions[knot_idx]);
-                    }
-                }
-            }
+    if !args.ropes.is_empty() {
+        let ropes = args
+            .input
+            .iter()
+            .map(|path| (path.clone(), Coords::default()))
+            .chain(args.ropes.iter().cloned())
+            .map(|(path, start)| {
+                let instructions = parse_input(&path).map_err(|err| format!("{path}: {err}"))?;
+                Ok(RopeInput { instructions, start })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let lockstep = lockstep::simulate_ropes(&ropes, num_knots).map_err(|err| err.to_string())?;
+        for collision in &lockstep.collisions {
+            let ropes = collision.ropes.iter().map(|rope_idx| rope_idx.to_string()).collect::<Vec<_>>().join(", ");
+            println!("tick {}: ({}, {}) ropes {ropes}", collision.tick, collision.cell.x, collision.cell.y);
+        }
+        for (rope_idx, visited) in lockstep.visited.iter().enumerate() {
+            println!("Rope {rope_idx} visited: {visited}.");
         }
+        return Ok(());
     }
 
-    tail_visited.len()
-}
-
-fn main() {
-    let instructions = parse_input("../inputs/d09");
-    let p1_ans = p1(&instructions);
-    println!("P1: {p1_ans}.");
+    let path = args.input.as_deref().unwrap_or(INPUT_PATH);
+    let instructions = parse_input(path).map_err(|err| format!("{path}: {err}"))?;
+    let out_of_bounds = |err: OutOfBounds| format!("{path}: {err}");
 
-    let p2_ans = p2(&instructions);
-    println!("P2: {p2_ans}.");
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
+    if let Some(map_path) = &args.walls {
+        let walls = obstacles::read_map(map_path)?;
+        let mut rope: Rope = Rope::tracking(num_knots, &[num_knots - 1]).with_obstacles(walls);
+        rope.run(&instructions).map_err(out_of_bounds)?;
+        for blocked in rope.blocked() {
+            println!("tick {}: knot {} blocked", blocked.tick, blocked.knot_idx);
+        }
+        println!("Visited: {}.", rope.visited(num_knots - 1).unwrap().len());
+        return Ok(());
+    }
 
-    #[test]
-    fn test_parsing() {
-        let test_instructions = parse_input("../inputs/d09_test");
-        assert_eq!(test_instructions.len(), 8);
-        assert_eq!(test_instructions[0], Instruction { steps: 4, direction: Direction::Right });
+    if args.slack.is_some() || args.metric.is_some() {
+        let (slack, metric) = (args.slack.unwrap_or(1), args.metric.unwrap_or_default());
+        let p1_ans = elastic_tail_visits(&instructions, 2, slack, metric).map_err(out_of_bounds)?;
+        println!("P1: {p1_ans}.");
 
-        let instructions = parse_input("../inputs/d09");
-        assert_eq!(instructions.len(), 2000);
+        let p2_ans = elastic_tail_visits(&instructions, 10, slack, metric).map_err(out_of_bounds)?;
+        println!("P2: {p2_ans}.");
+        return Ok(());
     }
 
-    #[test]
-    fn test_is_adjacent() {
-        assert_eq!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 1, y: 1 }), true);
-        assert_eq!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 2, y: 1 }), true);
-        assert_eq!(Coords { x: 1, y: 2 }.is_adjacent(Coords { x: 2, y: 1 }), true);
-        assert_eq!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 1, y: 1 }), true);
+    if !args.draw && !args.stats && args.heatmap.is_none() {
+        let p1_ans = p1(&instructions).map_err(out_of_bounds)?;
+        println!("P1: {p1_ans}.");
 
-        assert_eq!(Coords { x: 2, y: 1 }.is_adjacent(Coords { x: 1, y: 3 }), false);
+        let p2_ans = p2(&instructions).map_err(out_of_bounds)?;
+        println!("P2: {p2_ans}.");
+        return Ok(());
     }
 
-    #[test]
-    fn test_move_in_direction() {
-        let zero = Coords::default();
-        assert_eq!(zero.move_in_direction(Direction::Up), Coords { x: 0, y: 1 });
-        assert_eq!(zero.move_in_direction(Direction::Down), Coords { x: 0, y: -1 });
-        assert_eq!(zero.move_in_direction(Direction::Left), Coords { x: -1, y: 0 });
-        assert_eq!(zero.move_in_direction(Direction::Right), Coords { x: 1, y: 0 });
+    if args.draw {
+        let mut rope: Rope = Rope::tracking(num_knots, &[num_knots - 1]);
+        rope.run(&instructions).map_err(out_of_bounds)?;
+        let visited = rope.visited(num_knots - 1).unwrap();
+        let bounds = Bounds::around(visited.iter().chain(rope.knots()));
+        println!("{}", render::render_ascii(bounds, visited, rope.knots()));
     }
 
-    #[test]
-    fn test_move_tail() {
-        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 2, y: 3 }), Coords { x: 2, y: 2 });
-        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 3, y: 2 }), Coords { x: 2, y: 2 });
-
-        assert_eq!(Coords { x: 1, y: 1 }.follow(Coords { x: 3, y: 1 }), Coords { x: 2, y: 1 });
-        assert_eq!(Coords { x: 1, y: 3 }.follow(Coords { x: 1, y: 1 }), Coords { x: 1, y: 2 });
-
-        assert_eq!(Coords { x: 1, y: 0 }.follow(Coords { x: 3, y: 0 }), Coords { x: 2, y: 0 });
+    if args.stats {
+        let (_, stats) = simulate_with_stats(&instructions, num_knots).map_err(out_of_bounds)?;
+        println!("{stats}");
     }
 
-    #[test]
-    fn test_p1() {
-        let test_instructions = parse_input("../inputs/d09_test");
-        assert_eq!(p1(&test_instructions), 13);
-
-        let instructions = parse_input("../inputs/d09");
-        assert_eq!(p1(&instructions), 6026);
+    if let Some(path) = &args.heatmap {
+        let knot_idx = args.knot.unwrap_or(num_knots - 1);
+        if knot_idx >= num_knots {
+            return Err(format!("--knot must be below the knot count {num_knots}"));
+        }
+        let counts = render::visit_counts(&instructions, num_knots, knot_idx).map_err(out_of_bounds)?;
+        let file = File::create(path).map_err(|err| format!("{path}: {err}"))?;
+        render::write_ppm(&counts, args.scale.unwrap_or(4).max(1), &mut BufWriter::new(file))
+            .map_err(|err| format!("{path}: {err}"))?;
     }
+    Ok(())
+}
 
-    #[test]
-    fn test_p2() {
-        let test_instructions = parse_input("../inputs/d09_test");
-        assert_eq!(p2(&test_instructions), 1);
-
-        let test_instructions2 = parse_input("../inputs/d09_test2");
-        assert_eq!(p2(&test_instructions2), 36);
-
-        let instructions = parse_input("../inputs/d09");
-        assert_eq!(p2(&instructions), 2273);
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(err) = result {
+        if !err.is_empty() {
+            eprintln!("{err}\n");
+        }
+        eprintln!("{USAGE}");
+        process::exit(2);
     }
 }
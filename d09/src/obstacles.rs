@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::{fmt, fs};
+
+use crate::Coords;
+
+/// Why a map of walls could not be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapError {
+    MissingStart,
+    MultipleStarts,
+    /// A character other than `#`, `.` or `s`, at a 1-based line and column.
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::MissingStart => write!(f, "the map has no start `s`"),
+            MapError::MultipleStarts => write!(f, "the map has more than one start `s`"),
+            MapError::UnexpectedChar { line, column, found } => {
+                write!(f, "line {line}, column {column}: unexpected {found:?}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
+/// Parses a map drawn the way [`render_ascii`](crate::render::render_ascii) draws the plane: `#` for a wall, `.` for
+/// a free cell and `s` for the start, which becomes the origin, with `y` growing upwards.
+///
+/// Returns the walls. Cells outside the drawn area are free.
+pub fn parse_map(text: &str) -> Result<HashSet<Coords>, MapError> {
+    let mut start = None;
+    let mut walls = vec![];
+    for (row, line) in text.lines().enumerate() {
+        for (column, found) in line.trim_end().chars().enumerate() {
+            match found {
+                '#' => walls.push((row, column)),
+                '.' => {}
+                's' if start.is_none() => start = Some((row, column)),
+                's' => return Err(MapError::MultipleStarts),
+                _ => return Err(MapError::UnexpectedChar { line: row + 1, column: column + 1, found }),
+            }
+        }
+    }
+
+    let (start_row, start_column) = start.ok_or(MapError::MissingStart)?;
+    Ok(walls
+        .into_iter()
+        .map(|(row, column)| Coords { x: column as i32 - start_column as i32, y: start_row as i32 - row as i32 })
+        .collect())
+}
+
+pub fn read_map(path: &str) -> Result<HashSet<Coords>, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+    parse_map(&text).map_err(|err| format!("{path}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elastic::Metric;
+    use crate::rope::{Blocked, Rope};
+    use crate::{parse_input, Direction, Instruction};
+
+    fn run(walls: HashSet<Coords>, instructions: &[Instruction], num_knots: usize) -> Rope {
+        let mut rope: Rope = Rope::tracking(num_knots, &[num_knots - 1]).with_obstacles(walls);
+        rope.run(instructions).unwrap();
+        rope
+    }
+
+    fn blocked(ticks: &[(usize, usize)]) -> Vec<Blocked> {
+        ticks.iter().map(|&(tick, knot_idx)| Blocked { tick, knot_idx }).collect()
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let walls = parse_map("..#\n.s.\n#..\n").unwrap();
+        assert_eq!(walls, HashSet::from([Coords { x: 1, y: 1 }, Coords { x: -1, y: -1 }]));
+        assert_eq!(parse_map("s"), Ok(HashSet::new()));
+
+        assert_eq!(parse_map("..#\n...\n"), Err(MapError::MissingStart));
+        assert_eq!(parse_map("s.\n.s\n"), Err(MapError::MultipleStarts));
+        assert_eq!(parse_map("s.\n.x\n"), Err(MapError::UnexpectedChar { line: 2, column: 2, found: 'x' }));
+    }
+
+    #[test]
+    fn test_no_walls_changes_nothing() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        assert_eq!(run(HashSet::new(), &instructions, 2).visited(1).unwrap().len(), 6026);
+        assert_eq!(run(HashSet::new(), &instructions, 10).visited(9).unwrap().len(), 2273);
+        assert!(run(HashSet::new(), &instructions, 10).blocked().is_empty());
+    }
+
+    #[test]
+    fn test_puzzle_example_with_walls() {
+        let walls = read_map("../inputs/d09_test_walls").unwrap();
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+
+        let rope = run(walls.clone(), &instructions, 2);
+        assert_eq!(rope.visited(1).unwrap().len(), 13);
+        assert_eq!(rope.blocked(), blocked(&[(8, 0), (13, 0), (14, 0), (15, 0), (16, 0)]));
+        assert_eq!(rope.knots(), [Coords { x: -2, y: 1 }, Coords { x: -3, y: 1 }]);
+
+        // The head's path is the same however long the rope, but here the second knot also has to go round a wall.
+        let rope = run(walls, &instructions, 10);
+        assert_eq!(rope.visited(9).unwrap().len(), 1);
+        assert_eq!(rope.blocked(), blocked(&[(8, 0), (13, 0), (14, 0), (15, 0), (16, 0), (17, 2)]));
+        let knots = [(-2, 1), (-3, 1), (-2, 1), (-1, 1), (0, 1), (0, 0), (0, 0), (0, 0), (0, 0), (0, 0)];
+        assert_eq!(rope.knots(), knots.map(|(x, y)| Coords { x, y }));
+    }
+
+    #[test]
+    fn test_knots_go_round_walls() {
+        // Following diagonally would enter the wall, so the tail steps up instead.
+        let instructions = [
+            Instruction { direction: Direction::Up, steps: 1 },
+            Instruction { direction: Direction::UpRight, steps: 1 },
+            Instruction { direction: Direction::Right, steps: 1 },
+        ];
+        let mut rope: Rope = Rope::new(2).with_obstacles(HashSet::from([Coords { x: 1, y: 1 }]));
+        rope.apply(instructions[0]).unwrap();
+        rope.apply(instructions[1]).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 1, y: 2 }, Coords { x: 0, y: 1 }]);
+        rope.apply(instructions[2]).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 2, y: 2 }, Coords { x: 1, y: 2 }]);
+        assert_eq!(rope.blocked(), blocked(&[(2, 1)]));
+
+        // Ticks count like collision ticks: a head blocked on its very first step reports tick 1.
+        let mut rope: Rope = Rope::new(1).with_obstacles(HashSet::from([Coords { x: 0, y: 1 }]));
+        rope.apply(Instruction { direction: Direction::Up, steps: 1 }).unwrap();
+        assert_eq!(rope.blocked(), blocked(&[(1, 0)]));
+        assert_eq!(rope.head(), Coords::default());
+    }
+
+    #[test]
+    fn test_boxed_in_knot_stays_put() {
+        // With every free neighbour of the tail further from the head, it waits.
+        let walls = HashSet::from([Coords { x: 1, y: 1 }, Coords { x: 1, y: 0 }, Coords { x: 0, y: 1 }]);
+        let instructions = [
+            Instruction { direction: Direction::UpLeft, steps: 1 },
+            Instruction { direction: Direction::UpRight, steps: 1 },
+            Instruction { direction: Direction::Right, steps: 3 },
+        ];
+        let mut rope: Rope = Rope::new(2).with_slack(&[2], Metric::Chebyshev).with_obstacles(walls);
+        rope.run(&instructions).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 3, y: 2 }, Coords::default()]);
+        assert_eq!(rope.blocked(), blocked(&[(5, 1)]));
+    }
+
+    #[test]
+    #[should_panic(expected = "inside a wall")]
+    fn test_start_must_be_free() {
+        let _: Rope = Rope::new(2).with_obstacles(HashSet::from([Coords::default()]));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+use crate::stats::simulate_with_stats;
+use crate::{Coords, Instruction, OutOfBounds};
+
+/// The inclusive rectangle of the plane to draw.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Coords,
+    pub max: Coords,
+}
+
+impl Bounds {
+    /// The smallest rectangle holding the origin and every given position.
+    pub fn around<'a>(positions: impl IntoIterator<Item = &'a Coords>) -> Bounds {
+        let mut bounds = Bounds { min: Coords::default(), max: Coords::default() };
+        for &Coords { x, y } in positions {
+            bounds.min = Coords { x: bounds.min.x.min(x), y: bounds.min.y.min(y) };
+            bounds.max = Coords { x: bounds.max.x.max(x), y: bounds.max.y.max(y) };
+        }
+        bounds
+    }
+
+    fn width(self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    fn height(self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    /// Every position, row by row from the top, left to right.
+    fn rows(self) -> impl Iterator<Item = impl Iterator<Item = Coords>> {
+        (self.min.y..=self.max.y).rev().map(move |y| (self.min.x..=self.max.x).map(move |x| Coords { x, y }))
+    }
+}
+
+fn knot_label(knot_idx: usize, num_knots: usize) -> char {
+    match knot_idx {
+        0 => 'H',
+        1 if num_knots == 2 => 'T',
+        1..=9 => char::from_digit(knot_idx as u32, 10).unwrap(),
+        _ => '*',
+    }
+}
+
+/// Draws the plane the way the puzzle does: `#` for visited positions, `s` for the start, and `.` elsewhere, with
+/// `y` growing upwards.
+///
+/// Knots are drawn on top, as `H` for the head and `1` to `9` for the rest (`T` for the tail of a two-knot rope, `*`
+/// past the ninth knot). Where knots overlap, the one closer to the head is shown.
+pub fn render_ascii(bounds: Bounds, visited: &HashSet<Coords>, knots: &[Coords]) -> String {
+    let mut labels = HashMap::new();
+    for (knot_idx, &knot) in knots.iter().enumerate().rev() {
+        labels.insert(knot, knot_label(knot_idx, knots.len()));
+    }
+
+    bounds
+        .rows()
+        .map(|row| {
+            row.map(|position| match labels.get(&position) {
+                Some(&label) => label,
+                None if position == Coords::default() => 's',
+                None if visited.contains(&position) => '#',
+                None => '.',
+            })
+            .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Counts, for every position, after how many head steps knot `knot_idx` was there, including steps where it
+/// stayed put.
+pub fn visit_counts(
+    instructions: &[Instruction],
+    num_knots: usize,
+    knot_idx: usize,
+) -> Result<HashMap<Coords, usize>, OutOfBounds> {
+    let (_, mut stats) = simulate_with_stats(instructions, num_knots)?;
+    Ok(stats.knots.swap_remove(knot_idx).frequency)
+}
+
+/// Shades a visit count from black (never visited) through red to white (the busiest position), on a log scale so
+/// that positions visited only a few times stay visible.
+fn heat_color(count: usize, max_count: usize) -> [u8; 3] {
+    if count == 0 {
+        return [0, 0, 0];
+    }
+    let heat = (count as f64).ln_1p() / (max_count as f64).ln_1p();
+    let red = 64.0 + 191.0 * (heat * 2.0).min(1.0);
+    let other = 255.0 * (heat * 2.0 - 1.0).max(0.0);
+    [red as u8, other as u8, other as u8]
+}
+
+/// Writes visit counts as a binary PPM heat map, `scale` pixels per position, oriented like [`render_ascii`].
+pub fn write_ppm(counts: &HashMap<Coords, usize>, scale: usize, out: &mut impl io::Write) -> io::Result<()> {
+    let bounds = Bounds::around(counts.keys());
+    let max_count = counts.values().copied().max().unwrap_or(0);
+
+    write!(out, "P6\n{} {}\n255\n", bounds.width() * scale, bounds.height() * scale)?;
+    for row in bounds.rows() {
+        let line = row
+            .flat_map(|position| {
+                let color = heat_color(counts.get(&position).copied().unwrap_or(0), max_count);
+                std::iter::repeat_n(color, scale).flatten()
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rope::Rope;
+    use crate::{parse_input, Direction};
+
+    const EXAMPLE_BOUNDS: Bounds = Bounds { min: Coords { x: 0, y: 0 }, max: Coords { x: 5, y: 4 } };
+
+    #[test]
+    fn test_renders_puzzle_examples() {
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+        let mut rope: Rope = Rope::new(2);
+        rope.run(&instructions).unwrap();
+        assert_eq!(
+            render_ascii(EXAMPLE_BOUNDS, rope.visited(1).unwrap(), &[]),
+            "..##..
+...##.
+.####.
+....#.
+s###.."
+        );
+
+        let mut rope: Rope = Rope::new(10);
+        rope.apply(Instruction { direction: Direction::Right, steps: 4 }).unwrap();
+        assert_eq!(
+            render_ascii(EXAMPLE_BOUNDS, &HashSet::new(), rope.knots()),
+            "......
+......
+......
+......
+4321H."
+        );
+
+        let mut rope: Rope = Rope::new(2);
+        rope.apply(Instruction { direction: Direction::Up, steps: 1 }).unwrap();
+        assert_eq!(render_ascii(Bounds::around(rope.knots()), &HashSet::new(), rope.knots()), "H\nT");
+    }
+
+    #[test]
+    fn test_bounds_fit_everything() {
+        let instructions = parse_input("../inputs/d09_test2").unwrap();
+        let mut rope: Rope = Rope::new(10);
+        rope.run(&instructions).unwrap();
+        let visited = rope.visited(9).unwrap();
+
+        let bounds = Bounds::around(visited.iter().chain(rope.knots()));
+        let grid = render_ascii(bounds, visited, &[]);
+        assert_eq!(grid.matches('#').count(), 35);
+        assert_eq!(grid.matches('s').count(), 1);
+        assert_eq!(grid.lines().count(), bounds.height());
+        assert!(grid.lines().all(|line| line.len() == bounds.width()));
+
+        let with_knots = render_ascii(bounds, visited, rope.knots());
+        assert_eq!(with_knots.matches('H').count(), 1);
+    }
+
+    #[test]
+    fn test_visit_counts() {
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+        let counts = visit_counts(&instructions, 2, 1).unwrap();
+        let total_steps = instructions.iter().map(|instruction| instruction.steps as usize).sum::<usize>();
+        assert_eq!(counts.values().sum::<usize>(), total_steps);
+        assert_eq!(counts.len(), 13);
+        // The tail only stays at the origin for the first step.
+        assert_eq!(counts[&Coords::default()], 1);
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let counts = HashMap::from([(Coords { x: 0, y: 0 }, 1), (Coords { x: 1, y: 1 }, 4)]);
+        let mut out = vec![];
+        write_ppm(&counts, 2, &mut out).unwrap();
+
+        let header = b"P6\n4 4\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        let pixels = &out[header.len()..];
+        assert_eq!(pixels.len(), 4 * 4 * 3);
+        // The top-right position is the busiest, the bottom-left one visited once, and the rest never.
+        assert_eq!(pixels[6..9], [255, 255, 255]);
+        assert_eq!(pixels[0..3], [0, 0, 0]);
+        let bottom_left = &pixels[3 * 4 * 3..3 * 4 * 3 + 3];
+        assert!(bottom_left[0] > 64 && bottom_left[0] < 255);
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::HashSet;
+
+use crate::elastic::{Elastic, Metric};
+use crate::visited::VisitedSet;
+use crate::{Coords, Instruction, OutOfBounds, Position};
+
+/// A rope of knots starting at the origin, where the head moves by instructions and every other knot follows the
+/// one before it.
+///
+/// The same rope runs in the plane with [`Coords`] or in space with [`Coords3`](crate::space::Coords3). Visited
+/// positions go into a [`HashSet`] unless another [`VisitedSet`], like [`TileSet`](crate::visited::TileSet), is
+/// chosen.
+///
+/// By default a knot moves as soon as it stops touching the one before it; [`Rope::with_slack`] makes the rope
+/// elastic instead, and [`Rope::with_obstacles`] puts walls in its way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rope<P: Position = Coords, S: VisitedSet<P> = HashSet<P>> {
+    knots: Vec<P>,
+    /// `visited[i]` holds every position knot `i` has been at, if knot `i` is tracked.
+    visited: Vec<Option<S>>,
+    elastic: Option<Elastic>,
+    obstacles: Option<HashSet<P>>,
+    /// Every time a knot's move ran into a wall, in order.
+    blocked: Vec<Blocked>,
+    /// The number of head steps taken so far.
+    ticks: usize,
+}
+
+/// A knot whose move ran into a wall.
+///
+/// A blocked head stays put. A blocked trailing knot takes the free neighbouring cell closest to the knot before it
+/// instead, or stays put if no free neighbour is any closer than where it is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Blocked {
+    /// How many ticks had passed once the head step it happened on was done, counted like
+    /// [`Collision::tick`](crate::lockstep::Collision::tick): 1 for the first step. Never 0, as the rope starts clear of
+    /// every wall.
+    pub tick: usize,
+    pub knot_idx: usize,
+}
+
+impl<P: Position, S: VisitedSet<P>> Rope<P, S> {
+    /// Creates a rope that tracks the visited positions of every knot.
+    pub fn new(num_knots: usize) -> Rope<P, S> {
+        Rope::tracking(num_knots, &(0..num_knots).collect::<Vec<_>>())
+    }
+
+    /// Creates a rope that only tracks the visited positions of the knots in `tracked` (0 is the head).
+    pub fn tracking(num_knots: usize, tracked: &[usize]) -> Rope<P, S> {
+        assert!(num_knots > 0, "a rope needs at least one knot");
+        assert!(
+            tracked.iter().all(|&knot_idx| knot_idx < num_knots),
+            "tracked knots must be below the knot count {num_knots}"
+        );
+
+        let mut visited = (0..num_knots).map(|_| None).collect::<Vec<_>>();
+        for &knot_idx in tracked {
+            let mut start = S::default();
+            start.insert(P::default());
+            visited[knot_idx] = Some(start);
+        }
+        Rope {
+            knots: vec![P::default(); num_knots],
+            visited,
+            elastic: None,
+            obstacles: None,
+            blocked: vec![],
+            ticks: 0,
+        }
+    }
+
+    /// Makes every segment elastic: knot `i + 1` only moves, by one step to a neighbouring position, once it is more
+    /// than `slack[i]` away from knot `i` in `metric`. See [`Metric::catch_up`] for which step it takes.
+    ///
+    /// A slack of 1 in [`Metric::Chebyshev`] behaves like the default rope.
+    pub fn with_slack(mut self, slack: &[u32], metric: Metric) -> Rope<P, S> {
+        assert_eq!(slack.len(), self.knots.len() - 1, "a rope needs one slack per segment");
+        self.elastic = Some(Elastic { slack: slack.to_vec(), metric });
+        self
+    }
+
+    /// Makes the cells in `obstacles` walls that no knot can enter; the origin must be free.
+    pub fn with_obstacles(mut self, obstacles: HashSet<P>) -> Rope<P, S> {
+        assert!(!obstacles.contains(&P::default()), "the rope can't start inside a wall");
+        self.obstacles = Some(obstacles);
+        self
+    }
+
+    /// Every time a knot ran into a wall so far.
+    pub fn blocked(&self) -> &[Blocked] {
+        &self.blocked
+    }
+
+    pub fn knots(&self) -> &[P] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> P {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> P {
+        self.knots[self.knots.len() - 1]
+    }
+
+    /// The positions knot `knot_idx` has visited, or `None` if it isn't tracked.
+    pub fn visited(&self, knot_idx: usize) -> Option<&S> {
+        self.visited.get(knot_idx)?.as_ref()
+    }
+
+    /// Moves the head one step in `direction` and lets the rest of the rope catch up.
+    ///
+    /// Returns [`OutOfBounds`], leaving the rope as it was, if the head would move past what a coordinate can hold.
+    pub fn step(&mut self, direction: P::Direction) -> Result<(), OutOfBounds> {
+        let head = self.knots[0].move_in_direction(direction).ok_or(OutOfBounds)?;
+        self.ticks += 1;
+        let tick = self.ticks;
+
+        if self.is_wall(head) {
+            self.blocked.push(Blocked { tick, knot_idx: 0 });
+        } else {
+            self.knots[0] = head;
+            self.record(0);
+        }
+
+        for knot_idx in 1..self.knots.len() {
+            let (target, knot) = (self.knots[knot_idx - 1], self.knots[knot_idx]);
+            let mut moved = match &self.elastic {
+                None if !target.is_adjacent(knot) => knot.follow(target),
+                Some(Elastic { slack, metric }) if !metric.within(knot, target, slack[knot_idx - 1]) => {
+                    metric.catch_up(knot, target, slack[knot_idx - 1])
+                }
+                _ => continue,
+            };
+            if self.is_wall(moved) {
+                self.blocked.push(Blocked { tick, knot_idx });
+                match self.detour(knot, target) {
+                    Some(detour) => moved = detour,
+                    None => continue,
+                }
+            }
+            self.knots[knot_idx] = moved;
+            self.record(knot_idx);
+        }
+        Ok(())
+    }
+
+    fn is_wall(&self, position: P) -> bool {
+        self.obstacles.as_ref().is_some_and(|obstacles| obstacles.contains(&position))
+    }
+
+    /// The free neighbour of `knot` closest to `target`, if any is closer than `knot` itself.
+    fn detour(&self, knot: P, target: P) -> Option<P> {
+        let squared_distance = |position: P| position.offsets(target).map(|offset| offset * offset).sum::<i32>();
+        knot.neighbours()
+            .filter(|&neighbour| !self.is_wall(neighbour) && squared_distance(neighbour) < squared_distance(knot))
+            .min_by_key(|&neighbour| squared_distance(neighbour))
+    }
+
+    /// Moves the head `instruction.steps` times.
+    ///
+    /// Once every knot sits exactly one step behind the one before it along the move direction, the whole rope moves
+    /// in lockstep, so the remaining steps are taken at once, with each tracked knot's path added as one line. Elastic
+    /// ropes and ropes among walls always go step by step.
+    ///
+    /// Stops with [`OutOfBounds`] at the first step the head can't take.
+    pub fn apply(&mut self, instruction: Instruction<P::Direction>) -> Result<(), OutOfBounds> {
+        let Instruction { direction, steps } = instruction;
+        for taken in 0..steps {
+            if self.elastic.is_none() && self.obstacles.is_none() && self.is_straight(direction) {
+                return self.fast_forward(direction, steps - taken);
+            }
+            self.step(direction)?;
+        }
+        Ok(())
+    }
+
+    /// Whether every knot is one step in `direction` behind the one before it.
+    fn is_straight(&self, direction: P::Direction) -> bool {
+        self.knots.windows(2).all(|pair| pair[1].move_in_direction(direction) == Some(pair[0]))
+    }
+
+    /// Leaves the rope as it was if the head can't take every step; the other knots trail it, so they can.
+    fn fast_forward(&mut self, direction: P::Direction, steps: u32) -> Result<(), OutOfBounds> {
+        self.knots[0].move_by(direction, steps).ok_or(OutOfBounds)?;
+        self.ticks += steps as usize;
+        for (knot, visited) in self.knots.iter_mut().zip(&mut self.visited) {
+            if let Some(visited) = visited {
+                visited.insert_line(*knot, direction, steps)?;
+            }
+            *knot = knot.move_by(direction, steps).ok_or(OutOfBounds)?;
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self, instructions: &[Instruction<P::Direction>]) -> Result<(), OutOfBounds> {
+        for &instruction in instructions {
+            self.apply(instruction)?;
+        }
+        Ok(())
+    }
+
+    /// Runs `instructions` one head step at a time, yielding the knots after every step.
+    ///
+    /// Visited sets are updated as the iterator advances, and a fully consumed simulation leaves the rope exactly as
+    /// [`Rope::run`] would. A step the head can't take yields [`OutOfBounds`] and ends the simulation.
+    pub fn simulate<'a>(&'a mut self, instructions: &'a [Instruction<P::Direction>]) -> Simulation<'a, P, S> {
+        Simulation { rope: self, instructions, instruction_idx: 0, steps_taken: 0 }
+    }
+
+    fn record(&mut self, knot_idx: usize) {
+        if let Some(visited) = &mut self.visited[knot_idx] {
+            visited.insert(self.knots[knot_idx]);
+        }
+    }
+}
+
+/// The rope right after one head step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tick<P> {
+    /// The index of the instruction the step belongs to.
+    pub instruction_idx: usize,
+    pub knots: Vec<P>,
+}
+
+/// An iterator over the [`Tick`]s of a rope running a list of instructions; see [`Rope::simulate`].
+pub struct Simulation<'a, P: Position, S: VisitedSet<P>> {
+    rope: &'a mut Rope<P, S>,
+    instructions: &'a [Instruction<P::Direction>],
+    instruction_idx: usize,
+    /// Steps already taken from `instructions[instruction_idx]`.
+    steps_taken: u32,
+}
+
+impl<P: Position, S: VisitedSet<P>> Iterator for Simulation<'_, P, S> {
+    type Item = Result<Tick<P>, OutOfBounds>;
+
+    fn next(&mut self) -> Option<Result<Tick<P>, OutOfBounds>> {
+        loop {
+            let instruction = self.instructions.get(self.instruction_idx)?;
+            if self.steps_taken < instruction.steps {
+                if let Err(err) = self.rope.step(instruction.direction) {
+                    self.instruction_idx = self.instructions.len();
+                    return Some(Err(err));
+                }
+                self.steps_taken += 1;
+                return Some(Ok(Tick { instruction_idx: self.instruction_idx, knots: self.rope.knots.clone() }));
+            }
+            self.instruction_idx += 1;
+            self.steps_taken = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Debug;
+
+    use crate::space::{Coords3, Direction3};
+    use crate::visited::TileSet;
+    use crate::{parse_input, Direction, MAX_STEPS};
+
+    fn assert_fast_forward_matches<P: Position + Debug, S: VisitedSet<P> + Eq + Debug>(
+        instructions: &[Instruction<P::Direction>],
+        num_knots: usize,
+    ) {
+        let mut fast: Rope<P, S> = Rope::new(num_knots);
+        fast.run(instructions).unwrap();
+        // Simulating never fast-forwards.
+        let mut slow: Rope<P, S> = Rope::new(num_knots);
+        slow.simulate(instructions).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(fast, slow);
+    }
+
+    #[test]
+    fn test_every_knot_is_tracked() {
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+        let mut rope: Rope = Rope::new(10);
+        rope.run(&instructions).unwrap();
+
+        assert_eq!(rope.visited(1).unwrap().len(), 13);
+        assert_eq!(rope.visited(9).unwrap().len(), 1);
+        assert_eq!(rope.head(), Coords { x: 2, y: 2 });
+        assert_eq!(rope.tail(), Coords::default());
+        assert!(rope.visited(10).is_none());
+
+        // Every knot is within reach of the one before it, so it visits no more positions than that knot.
+        for knot_idx in 1..10 {
+            assert!(rope.visited(knot_idx).unwrap().len() <= rope.visited(knot_idx - 1).unwrap().len());
+        }
+    }
+
+    #[test]
+    fn test_middle_knot_matches_shorter_rope() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        let mut long: Rope = Rope::tracking(10, &[4]);
+        long.run(&instructions).unwrap();
+        assert!(long.visited(0).is_none());
+
+        let mut short: Rope = Rope::tracking(5, &[4]);
+        short.run(&instructions).unwrap();
+        assert_eq!(long.visited(4), short.visited(4));
+        assert_eq!(long.knots()[..5], short.knots()[..]);
+    }
+
+    #[test]
+    fn test_single_knot() {
+        let mut rope: Rope = Rope::new(1);
+        rope.run(&parse_input("../inputs/d09_test").unwrap()).unwrap();
+        assert_eq!(rope.head(), rope.tail());
+        assert!(rope.visited(0).unwrap().len() > 1);
+    }
+
+    #[test]
+    fn test_diagonal_head_moves() {
+        let mut rope: Rope = Rope::new(3);
+        rope.apply(Instruction { direction: Direction::UpRight, steps: 3 }).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 3, y: 3 }, Coords { x: 2, y: 2 }, Coords { x: 1, y: 1 }]);
+
+        rope.apply(Instruction { direction: Direction::DownRight, steps: 2 }).unwrap();
+        assert_eq!(rope.knots(), [Coords { x: 5, y: 1 }, Coords { x: 4, y: 1 }, Coords { x: 3, y: 1 }]);
+        assert_eq!(rope.visited(2).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_knots_stay_attached_on_diagonal_moves() {
+        let mut rope: Rope = Rope::new(10);
+        for instruction in parse_input("../inputs/d09_test_diagonal").unwrap() {
+            for _ in 0..instruction.steps {
+                let before = rope.knots().to_vec();
+                rope.step(instruction.direction).unwrap();
+                for (knots, previous) in rope.knots().windows(2).zip(&before[1..]) {
+                    assert!(knots[1].is_adjacent(knots[0]));
+                    assert!(knots[1].is_adjacent(*previous));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fast_forward_matches_stepping() {
+        let long_moves = [
+            Instruction { direction: Direction::Right, steps: 1000 },
+            Instruction { direction: Direction::UpLeft, steps: 500 },
+            Instruction { direction: Direction::Down, steps: 70 },
+            Instruction { direction: Direction::DownRight, steps: 3 },
+            Instruction { direction: Direction::Left, steps: 200 },
+        ];
+        for num_knots in [1, 2, 10, 30] {
+            assert_fast_forward_matches::<Coords, HashSet<Coords>>(&long_moves, num_knots);
+            assert_fast_forward_matches::<Coords, TileSet>(&long_moves, num_knots);
+            assert_fast_forward_matches::<Coords, HashSet<Coords>>(&parse_input("../inputs/d09").unwrap(), num_knots);
+            assert_fast_forward_matches::<Coords, HashSet<Coords>>(
+                &parse_input("../inputs/d09_test_diagonal").unwrap(),
+                num_knots,
+            );
+        }
+
+        for stretch in [3, 20] {
+            let walk = parse_input("../inputs/d09")
+                .unwrap()
+                .into_iter()
+                .map(|instruction| Instruction { steps: instruction.steps * stretch, ..instruction })
+                .collect::<Vec<_>>();
+            assert_fast_forward_matches::<Coords, TileSet>(&walk, 10);
+        }
+
+        let moves3 = [
+            Instruction { direction: Direction3::Forward, steps: 40 },
+            Instruction { direction: Direction3::Up, steps: 5 },
+            Instruction { direction: Direction3::Back, steps: 60 },
+        ];
+        assert_fast_forward_matches::<Coords3, HashSet<Coords3>>(&moves3, 10);
+    }
+
+    #[test]
+    fn test_fast_forward_skips_straight_stretches() {
+        let mut rope: Rope<Coords, TileSet> = Rope::tracking(10, &[9]);
+        rope.apply(Instruction { direction: Direction::Right, steps: 9 }).unwrap();
+        assert!(rope.is_straight(Direction::Right));
+        assert!(!rope.is_straight(Direction::Up));
+
+        rope.apply(Instruction { direction: Direction::Right, steps: 1_000_000 }).unwrap();
+        assert_eq!(rope.head(), Coords { x: 1_000_009, y: 0 });
+        assert_eq!(rope.tail(), Coords { x: 1_000_000, y: 0 });
+        assert_eq!(rope.visited(9).unwrap().len(), 1_000_001);
+        assert!(rope.visited(9).unwrap().contains(&Coords { x: 777_777, y: 0 }));
+    }
+
+    #[test]
+    fn test_simulate() {
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+        let mut rope: Rope = Rope::new(10);
+        let ticks = rope.simulate(&instructions).collect::<Result<Vec<_>, _>>().unwrap();
+
+        let total_steps = instructions.iter().map(|instruction| instruction.steps as usize).sum::<usize>();
+        assert_eq!(ticks.len(), total_steps);
+        assert_eq!(
+            ticks[0],
+            Tick { instruction_idx: 0, knots: [vec![Coords { x: 1, y: 0 }], vec![Coords::default(); 9]].concat() }
+        );
+        assert_eq!(
+            ticks[3].knots[..5],
+            [
+                Coords { x: 4, y: 0 },
+                Coords { x: 3, y: 0 },
+                Coords { x: 2, y: 0 },
+                Coords { x: 1, y: 0 },
+                Coords::default()
+            ]
+        );
+        assert_eq!(ticks[4].instruction_idx, 1);
+        assert_eq!(ticks.last().unwrap().instruction_idx, instructions.len() - 1);
+        assert!(ticks.windows(2).all(|pair| pair[0].instruction_idx <= pair[1].instruction_idx));
+
+        let mut run: Rope = Rope::new(10);
+        run.run(&instructions).unwrap();
+        assert_eq!(rope, run);
+        assert_eq!(ticks.last().unwrap().knots, run.knots());
+    }
+
+    #[test]
+    fn test_simulate_skips_empty_instructions() {
+        let instructions = [
+            Instruction { direction: Direction::Up, steps: 0 },
+            Instruction { direction: Direction::Right, steps: 2 },
+            Instruction { direction: Direction::Left, steps: 0 },
+            Instruction { direction: Direction::Up, steps: 1 },
+        ];
+        let mut rope: Rope = Rope::new(2);
+        let indices = rope.simulate(&instructions).map(|tick| tick.unwrap().instruction_idx).collect::<Vec<_>>();
+        assert_eq!(indices, [1, 1, 3]);
+        assert!(rope.simulate(&[]).next().is_none());
+    }
+
+    #[test]
+    fn test_moves_past_the_edge_are_errors() {
+        // Nothing is tracked, so reaching the edge doesn't mean filling in billions of positions.
+        let mut rope: Rope = Rope::tracking(2, &[]);
+        rope.apply(Instruction { direction: Direction::Right, steps: MAX_STEPS }).unwrap();
+        let knots = [Coords { x: i32::MAX, y: 0 }, Coords { x: i32::MAX - 1, y: 0 }];
+        assert_eq!(rope.knots(), knots);
+
+        // Neither a fast-forward nor a single step moves the rope once the head can't follow.
+        assert_eq!(rope.apply(Instruction { direction: Direction::Right, steps: 2 }), Err(OutOfBounds));
+        assert_eq!(rope.step(Direction::UpRight), Err(OutOfBounds));
+        assert_eq!(rope.knots(), knots);
+
+        let instructions =
+            [Instruction { direction: Direction::Up, steps: 1 }, Instruction { direction: Direction::Right, steps: 3 }];
+        let ticks = rope.simulate(&instructions).collect::<Vec<_>>();
+        assert_eq!(ticks.len(), 2);
+        assert!(ticks[0].is_ok());
+        assert_eq!(ticks[1], Err(OutOfBounds));
+    }
+}
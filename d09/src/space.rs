@@ -0,0 +1,186 @@
+use std::io;
+
+use crate::{parse_instructions, Instruction, Position};
+
+/// A head move in space: the plane's four moves plus forward and back along the `z` axis.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction3 {
+    Left,
+    Right,
+    Up,
+    Down,
+    Forward,
+    Back,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Coords3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Position for Coords3 {
+    type Direction = Direction3;
+
+    /// Two positions touch when they are at most one unit apart on every axis, so each has 26 neighbours.
+    fn is_adjacent(self, another: Coords3) -> bool {
+        (self.x - another.x).abs() <= 1 && (self.y - another.y).abs() <= 1 && (self.z - another.z).abs() <= 1
+    }
+
+    fn move_in_direction(self, direction: Direction3) -> Option<Coords3> {
+        self.move_by(direction, 1)
+    }
+
+    fn move_by(self, direction: Direction3, steps: u32) -> Option<Coords3> {
+        let steps = i32::try_from(steps).ok()?;
+        let Coords3 { x, y, z } = self;
+        Some(match direction {
+            Direction3::Right => Coords3 { x: x.checked_add(steps)?, y, z },
+            Direction3::Left => Coords3 { x: x.checked_sub(steps)?, y, z },
+            Direction3::Up => Coords3 { x, y: y.checked_add(steps)?, z },
+            Direction3::Down => Coords3 { x, y: y.checked_sub(steps)?, z },
+            Direction3::Forward => Coords3 { x, y, z: z.checked_add(steps)? },
+            Direction3::Back => Coords3 { x, y, z: z.checked_sub(steps)? },
+        })
+    }
+
+    /// Moves one unit towards `target` on every axis where they differ.
+    fn follow(self, target: Coords3) -> Coords3 {
+        self.step_towards(target)
+    }
+
+    fn step_towards(self, target: Coords3) -> Coords3 {
+        Coords3 {
+            x: self.x + (target.x - self.x).signum(),
+            y: self.y + (target.y - self.y).signum(),
+            z: self.z + (target.z - self.z).signum(),
+        }
+    }
+
+    fn offsets(self, other: Coords3) -> impl Iterator<Item = i32> {
+        [self.x - other.x, self.y - other.y, self.z - other.z].into_iter()
+    }
+
+    fn neighbours(self) -> impl Iterator<Item = Coords3> {
+        (-1..=1)
+            .flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy, dz))))
+            .filter(|&offset| offset != (0, 0, 0))
+            .filter_map(move |(dx, dy, dz)| {
+                Some(Coords3 { x: self.x.checked_add(dx)?, y: self.y.checked_add(dy)?, z: self.z.checked_add(dz)? })
+            })
+    }
+}
+
+/// Reads the 3D instruction format: `L/R/U/D` as in the plane, plus `F` and `B` for forward and back.
+pub fn parse_input3(path: &str) -> io::Result<Vec<Instruction<Direction3>>> {
+    parse_instructions(path, |direction| match direction {
+        "L" => Some(Direction3::Left),
+        "R" => Some(Direction3::Right),
+        "U" => Some(Direction3::Up),
+        "D" => Some(Direction3::Down),
+        "F" => Some(Direction3::Forward),
+        "B" => Some(Direction3::Back),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use crate::rope::Rope;
+    use crate::{parse_input, tail_visits, Coords, Direction};
+
+    #[test]
+    fn test_parsing() {
+        let instructions = parse_input3("../inputs/d09_test_3d").unwrap();
+        assert_eq!(instructions.len(), 8);
+        assert_eq!(instructions[0], Instruction { direction: Direction3::Right, steps: 4 });
+        assert_eq!(instructions[1], Instruction { direction: Direction3::Forward, steps: 4 });
+    }
+
+    #[test]
+    fn test_is_adjacent() {
+        let zero = Coords3::default();
+        let mut neighbours = 0;
+        for x in -2..=2 {
+            for y in -2..=2 {
+                for z in -2..=2 {
+                    if zero.is_adjacent(Coords3 { x, y, z }) {
+                        neighbours += 1;
+                    }
+                }
+            }
+        }
+        // The 26 neighbours and the position itself.
+        assert_eq!(neighbours, 27);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let position = Coords3 { x: 1, y: -2, z: 3 };
+        let neighbours = position.neighbours().collect::<HashSet<_>>();
+        assert_eq!(neighbours.len(), 26);
+        assert!(neighbours.iter().all(|&neighbour| neighbour != position && neighbour.is_adjacent(position)));
+
+        // A corner of the coordinate range only has the neighbours inside it.
+        let corner = Coords3 { x: i32::MAX, y: i32::MAX, z: i32::MIN };
+        assert_eq!(corner.neighbours().count(), 7);
+        assert_eq!(corner.move_in_direction(Direction3::Back), None);
+        assert_eq!(corner.move_by(Direction3::Left, 2), Some(Coords3 { x: i32::MAX - 2, y: i32::MAX, z: i32::MIN }));
+    }
+
+    #[test]
+    fn test_follow() {
+        let zero = Coords3::default();
+        assert_eq!(zero.follow(Coords3 { x: 2, y: 0, z: 0 }), Coords3 { x: 1, y: 0, z: 0 });
+        assert_eq!(zero.follow(Coords3 { x: 2, y: 1, z: 0 }), Coords3 { x: 1, y: 1, z: 0 });
+        assert_eq!(zero.follow(Coords3 { x: 1, y: -1, z: 2 }), Coords3 { x: 1, y: -1, z: 1 });
+        assert_eq!(zero.follow(Coords3 { x: -2, y: 2, z: -2 }), Coords3 { x: -1, y: 1, z: -1 });
+    }
+
+    #[test]
+    fn test_planar_moves_match_2d() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        let instructions3 = instructions
+            .iter()
+            .map(|&Instruction { direction, steps }| {
+                let direction = match direction {
+                    Direction::Left => Direction3::Left,
+                    Direction::Right => Direction3::Right,
+                    Direction::Up => Direction3::Up,
+                    Direction::Down => Direction3::Down,
+                    _ => unreachable!("the puzzle input has no diagonal moves"),
+                };
+                Instruction { direction, steps }
+            })
+            .collect::<Vec<_>>();
+
+        let mut rope: Rope<Coords> = Rope::new(10);
+        rope.run(&instructions).unwrap();
+        let mut rope3: Rope<Coords3> = Rope::new(10);
+        rope3.run(&instructions3).unwrap();
+
+        for knot_idx in 0..10 {
+            assert_eq!(rope3.visited(knot_idx).unwrap().len(), rope.visited(knot_idx).unwrap().len());
+        }
+        assert!(rope3.knots().iter().all(|knot| knot.z == 0));
+    }
+
+    #[test]
+    fn test_rope_in_space() {
+        let instructions = parse_input3("../inputs/d09_test_3d").unwrap();
+        assert_eq!(tail_visits::<Coords3>(&instructions, 2), Ok(20));
+
+        let mut rope: Rope<Coords3> = Rope::tracking(10, &[1, 4, 9]);
+        rope.run(&instructions).unwrap();
+        assert_eq!(rope.visited(1).unwrap().len(), 20);
+        assert_eq!(rope.visited(4).unwrap().len(), 6);
+        assert_eq!(rope.visited(9).unwrap().len(), 1);
+        for knots in rope.knots().windows(2) {
+            assert!(knots[1].is_adjacent(knots[0]));
+        }
+    }
+}
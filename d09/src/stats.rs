@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::rope::{Rope, Tick};
+use crate::{Coords, Instruction, OutOfBounds};
+
+/// How one knot moved over a simulation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KnotStats {
+    /// For every position, after how many head steps the knot was there.
+    pub frequency: HashMap<Coords, usize>,
+    /// The most consecutive head steps during which the knot did not move.
+    pub longest_stationary: usize,
+    /// Moves along one axis and moves along both. For the head these are its own moves, for every other knot the
+    /// moves `follow` made.
+    pub straight_moves: usize,
+    pub diagonal_moves: usize,
+}
+
+/// Per-knot statistics, collected step by step alongside the visited sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RopeStats {
+    pub knots: Vec<KnotStats>,
+    /// The number of head steps taken.
+    pub steps: usize,
+}
+
+impl RopeStats {
+    /// The position knot `knot_idx` spent the most steps at, and how many; `None` before the first step.
+    pub fn busiest(&self, knot_idx: usize) -> Option<(Coords, usize)> {
+        let frequency = &self.knots[knot_idx].frequency;
+        // Break ties by position, so the answer doesn't depend on hashing order.
+        frequency
+            .iter()
+            .map(|(&position, &count)| (position, count))
+            .max_by_key(|&(position, count)| (count, std::cmp::Reverse((position.y, position.x))))
+    }
+}
+
+/// Runs a rope of `num_knots` knots that tracks every knot's visited set, collecting [`RopeStats`] along the way.
+pub fn simulate_with_stats(instructions: &[Instruction], num_knots: usize) -> Result<(Rope, RopeStats), OutOfBounds> {
+    let mut rope: Rope = Rope::new(num_knots);
+    let mut stats = RopeStats { knots: vec![KnotStats::default(); num_knots], steps: 0 };
+    let mut stationary = vec![0; num_knots];
+
+    let mut before = rope.knots().to_vec();
+    for tick in rope.simulate(instructions) {
+        let Tick { knots, .. } = tick?;
+        stats.steps += 1;
+
+        for (knot_idx, (&old, &new)) in before.iter().zip(&knots).enumerate() {
+            let knot = &mut stats.knots[knot_idx];
+            *knot.frequency.entry(new).or_default() += 1;
+
+            if old == new {
+                stationary[knot_idx] += 1;
+                knot.longest_stationary = knot.longest_stationary.max(stationary[knot_idx]);
+            } else {
+                stationary[knot_idx] = 0;
+                if old.x != new.x && old.y != new.y {
+                    knot.diagonal_moves += 1;
+                } else {
+                    knot.straight_moves += 1;
+                }
+            }
+        }
+        before = knots;
+    }
+
+    Ok((rope, stats))
+}
+
+/// Prints the statistics as a table with one row per knot.
+impl fmt::Display for RopeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "knot  cells  longest still  straight  diagonal  busiest")?;
+        for (knot_idx, knot) in self.knots.iter().enumerate() {
+            let busiest = match self.busiest(knot_idx) {
+                Some((Coords { x, y }, count)) => format!("({x}, {y}) x{count}"),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:>4}  {:>5}  {:>13}  {:>8}  {:>8}  {busiest}",
+                knot_idx,
+                knot.frequency.len(),
+                knot.longest_stationary,
+                knot.straight_moves,
+                knot.diagonal_moves,
+            )?;
+        }
+        write!(f, "{} steps", self.steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{p1, p2, parse_input, Direction};
+
+    #[test]
+    fn test_counts_add_up() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        let (rope, stats) = simulate_with_stats(&instructions, 10).unwrap();
+        assert_eq!(Ok(rope.visited(1).unwrap().len()), p1(&instructions));
+        assert_eq!(Ok(rope.visited(9).unwrap().len()), p2(&instructions));
+
+        let total_steps = instructions.iter().map(|instruction| instruction.steps as usize).sum::<usize>();
+        assert_eq!(stats.steps, total_steps);
+        for (knot_idx, knot) in stats.knots.iter().enumerate() {
+            assert_eq!(knot.frequency.values().sum::<usize>(), total_steps);
+            // Frequencies only count positions after a step, so the origin may be missing from them.
+            let visited = rope.visited(knot_idx).unwrap();
+            assert!(knot.frequency.keys().all(|position| visited.contains(position)));
+            assert!(visited.len() - knot.frequency.len() <= 1);
+            assert!(knot.longest_stationary <= total_steps);
+        }
+
+        // The puzzle input only has straight head moves, so only followers move diagonally.
+        assert_eq!((stats.knots[0].straight_moves, stats.knots[0].diagonal_moves), (total_steps, 0));
+        assert!(stats.knots[1].diagonal_moves > 0);
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+        let (_, stats) = simulate_with_stats(&instructions, 2).unwrap();
+        let tail = &stats.knots[1];
+        // The tail moves once for each of its 12 new positions, plus once back onto one it had visited.
+        assert_eq!((tail.straight_moves, tail.diagonal_moves), (9, 4));
+        assert_eq!(tail.longest_stationary, 3);
+
+        // The 10-knot tail never leaves the origin.
+        let (_, stats) = simulate_with_stats(&instructions, 10).unwrap();
+        assert_eq!(stats.knots[9].longest_stationary, 24);
+        assert_eq!(stats.busiest(9), Some((Coords::default(), 24)));
+    }
+
+    #[test]
+    fn test_diagonal_head_moves_are_counted() {
+        let instructions = [
+            Instruction { direction: Direction::UpRight, steps: 3 },
+            Instruction { direction: Direction::Left, steps: 2 },
+        ];
+        let (_, stats) = simulate_with_stats(&instructions, 2).unwrap();
+        assert_eq!((stats.knots[0].straight_moves, stats.knots[0].diagonal_moves), (2, 3));
+        assert_eq!((stats.knots[1].straight_moves, stats.knots[1].diagonal_moves), (0, 2));
+        assert_eq!(stats.knots[1].longest_stationary, 2);
+        assert_eq!(stats.busiest(1), Some((Coords { x: 2, y: 2 }, 3)));
+    }
+}
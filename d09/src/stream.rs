@@ -0,0 +1,109 @@
+use std::io::{self, BufRead};
+
+use crate::rope::Rope;
+use crate::visited::{TileSet, VisitedSet};
+use crate::{incorrect_input, parse_direction, parse_instruction, Coords};
+
+/// How far a stream has got: instructions applied so far, and the positions the tail has visited.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub instructions: usize,
+    pub visited: usize,
+}
+
+/// Reads instructions line by line and moves a rope of `num_knots` knots as they come, calling `report` after every
+/// `every` instructions (and never if `every` is 0).
+///
+/// Only the rope and the tail's visited set are kept, never the instructions, and the set is a [`TileSet`], so
+/// arbitrarily long inputs run in memory proportional to the visited area. Blank lines are skipped; a malformed line,
+/// or one that moves the rope further than a coordinate can hold, stops the stream with an
+/// [`io::ErrorKind::InvalidData`] error naming its 1-based line number.
+pub fn stream<R: BufRead>(
+    mut reader: R,
+    num_knots: usize,
+    every: usize,
+    mut report: impl FnMut(Progress),
+) -> io::Result<Progress> {
+    let tail_idx = num_knots - 1;
+    let mut rope: Rope<Coords, TileSet> = Rope::tracking(num_knots, &[tail_idx]);
+    let mut progress = Progress { instructions: 0, visited: 1 };
+
+    let mut line = String::new();
+    let mut line_num = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(progress);
+        }
+        line_num += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let instruction = parse_instruction(&line, parse_direction).ok_or_else(|| incorrect_input(line_num, &line))?;
+        rope.apply(instruction)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("line {line_num}: {err}")))?;
+
+        progress = Progress { instructions: progress.instructions + 1, visited: rope.visited(tail_idx).unwrap().len() };
+        if progress.instructions.is_multiple_of(every) {
+            report(progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use crate::{p1, p2, parse_input};
+
+    fn stream_file(path: &str, num_knots: usize, every: usize) -> (Progress, Vec<Progress>) {
+        let mut reports = vec![];
+        let reader = BufReader::new(File::open(path).unwrap());
+        let progress = stream(reader, num_knots, every, |progress| reports.push(progress)).unwrap();
+        (progress, reports)
+    }
+
+    #[test]
+    fn test_matches_batch_answers() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        let (progress, reports) = stream_file("../inputs/d09", 2, 500);
+        assert_eq!(progress, Progress { instructions: 2000, visited: p1(&instructions).unwrap() });
+        assert_eq!(reports.iter().map(|progress| progress.instructions).collect::<Vec<_>>(), [500, 1000, 1500, 2000]);
+        assert_eq!(reports[3], progress);
+
+        let (progress, _) = stream_file("../inputs/d09", 10, 0);
+        assert_eq!(progress.visited, p2(&instructions).unwrap());
+    }
+
+    #[test]
+    fn test_running_counts() {
+        let instructions = parse_input("../inputs/d09_test").unwrap();
+        let (_, reports) = stream_file("../inputs/d09_test", 2, 1);
+        for (count, progress) in reports.iter().enumerate() {
+            assert_eq!(progress.visited, p1(&instructions[..=count]).unwrap());
+        }
+        assert!(reports.windows(2).all(|pair| pair[0].visited <= pair[1].visited));
+    }
+
+    #[test]
+    fn test_blank_and_malformed_lines() {
+        let progress = stream("R 4\n\nU 4\r\n".as_bytes(), 2, 0, |_| {}).unwrap();
+        assert_eq!(progress, Progress { instructions: 2, visited: 7 });
+
+        let err = stream("R 4\nU\nL 3\n".as_bytes(), 2, 0, |_| {}).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: incorrect input \"U\"");
+        assert!(stream("X 1\n".as_bytes(), 2, 0, |_| {}).is_err());
+        assert!(stream("R 1 2\n".as_bytes(), 2, 0, |_| {}).is_err());
+
+        // More steps than a coordinate can hold are rejected rather than wrapped.
+        let err = stream("R 3000000000\n".as_bytes(), 1, 0, |_| {}).unwrap_err();
+        assert_eq!(err.to_string(), "line 1: incorrect input \"R 3000000000\"");
+        let err = stream("L 5\nL 2147483647\n".as_bytes(), 1, 0, |_| {}).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "line 2: the rope moves further than a coordinate can hold");
+    }
+}
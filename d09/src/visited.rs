@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Coords, Direction, OutOfBounds, Position};
+
+/// Storage for the positions a knot has visited.
+pub trait VisitedSet<P: Position>: Default {
+    /// Adds a position, returning whether it was new.
+    fn insert(&mut self, position: P) -> bool;
+
+    fn contains(&self, position: &P) -> bool;
+
+    /// The number of distinct positions visited.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds the `steps` positions after `start` in `direction`, excluding `start` itself.
+    ///
+    /// Returns [`OutOfBounds`] without adding anything if the line runs past what a coordinate can hold.
+    fn insert_line(&mut self, start: P, direction: P::Direction, steps: u32) -> Result<(), OutOfBounds> {
+        insert_each(self, start, direction, steps)
+    }
+}
+
+fn insert_each<P: Position>(
+    set: &mut impl VisitedSet<P>,
+    start: P,
+    direction: P::Direction,
+    steps: u32,
+) -> Result<(), OutOfBounds> {
+    start.move_by(direction, steps).ok_or(OutOfBounds)?;
+    let mut position = start;
+    for _ in 0..steps {
+        position = position.move_in_direction(direction).ok_or(OutOfBounds)?;
+        set.insert(position);
+    }
+    Ok(())
+}
+
+impl<P: Position> VisitedSet<P> for HashSet<P> {
+    fn insert(&mut self, position: P) -> bool {
+        HashSet::insert(self, position)
+    }
+
+    fn contains(&self, position: &P) -> bool {
+        HashSet::contains(self, position)
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+}
+
+const TILE_SHIFT: i32 = 6;
+const TILE_SIZE: i32 = 1 << TILE_SHIFT;
+
+/// A set of planar positions stored as 64×64 bitmap tiles, allocated as the walk reaches them.
+///
+/// A tile takes 512 bytes for 4096 positions, against the 8 bytes of key plus table overhead a [`HashSet`] spends on
+/// every single position, so dense walks take a fraction of the memory. Tiles are keyed by their corner, so the set
+/// grows in any direction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TileSet {
+    tiles: HashMap<Coords, Box<[u64; TILE_SIZE as usize]>>,
+    len: usize,
+}
+
+impl TileSet {
+    /// Splits a position into its tile, its row within the tile, and its bit within that row.
+    fn locate(position: Coords) -> (Coords, usize, u64) {
+        let tile = Coords { x: position.x >> TILE_SHIFT, y: position.y >> TILE_SHIFT };
+        let row = (position.y & (TILE_SIZE - 1)) as usize;
+        let bit = 1 << (position.x & (TILE_SIZE - 1));
+        (tile, row, bit)
+    }
+
+    /// The number of tiles allocated so far.
+    pub fn num_tiles(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Every visited position, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = Coords> + '_ {
+        self.tiles.iter().flat_map(|(tile, rows)| {
+            rows.iter().enumerate().flat_map(move |(row_idx, &row)| {
+                (0..TILE_SIZE).filter(move |column| row & (1 << column) != 0).map(move |column| Coords {
+                    x: (tile.x << TILE_SHIFT) + column,
+                    y: (tile.y << TILE_SHIFT) + row_idx as i32,
+                })
+            })
+        })
+    }
+}
+
+impl VisitedSet<Coords> for TileSet {
+    fn insert(&mut self, position: Coords) -> bool {
+        let (tile, row, bit) = TileSet::locate(position);
+        let rows = self.tiles.entry(tile).or_insert_with(|| Box::new([0; TILE_SIZE as usize]));
+        let is_new = rows[row] & bit == 0;
+        rows[row] |= bit;
+        self.len += is_new as usize;
+        is_new
+    }
+
+    fn contains(&self, position: &Coords) -> bool {
+        let (tile, row, bit) = TileSet::locate(*position);
+        self.tiles.get(&tile).is_some_and(|rows| rows[row] & bit != 0)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Sets horizontal lines a whole tile row at a time; other directions go position by position.
+    fn insert_line(&mut self, start: Coords, direction: Direction, steps: u32) -> Result<(), OutOfBounds> {
+        if steps == 0 {
+            return Ok(());
+        }
+        let end = start.move_by(direction, steps).ok_or(OutOfBounds)?;
+        let (min_x, max_x) = match direction {
+            Direction::Right => (start.x + 1, end.x),
+            Direction::Left => (end.x, start.x - 1),
+            _ => return insert_each(self, start, direction, steps),
+        };
+
+        let mut x = min_x;
+        while x <= max_x {
+            let (tile, row, _) = TileSet::locate(Coords { x, y: start.y });
+            let first_column = x & (TILE_SIZE - 1);
+            let last_column = first_column + (max_x - x).min(TILE_SIZE - 1 - first_column);
+            let width = last_column - first_column + 1;
+            let mask = if width == TILE_SIZE { !0 } else { ((1 << width) - 1) << first_column };
+
+            let rows = self.tiles.entry(tile).or_insert_with(|| Box::new([0; TILE_SIZE as usize]));
+            self.len += (mask & !rows[row]).count_ones() as usize;
+            rows[row] |= mask;
+            let Some(next) = x.checked_add(width) else { break };
+            x = next;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rope::Rope;
+    use crate::{parse_input, Instruction};
+
+    #[test]
+    fn test_tile_set_grows_in_every_direction() {
+        let mut set = TileSet::default();
+        assert!(set.is_empty());
+        for position in [(0, 0), (-1, -1), (63, 63), (64, 0), (-64, 64), (-65, -1000), (1 << 20, -(1 << 20))] {
+            let position = Coords { x: position.0, y: position.1 };
+            assert!(!set.contains(&position));
+            assert!(set.insert(position));
+            assert!(!set.insert(position));
+            assert!(set.contains(&position));
+        }
+        assert_eq!(set.len(), 7);
+        assert_eq!(set.num_tiles(), 6);
+        assert!(!set.contains(&Coords { x: 1, y: 0 }));
+        assert!(!set.contains(&Coords { x: -1, y: 0 }));
+
+        let mut positions = set.iter().collect::<Vec<_>>();
+        positions.sort_by_key(|position| (position.x, position.y));
+        assert_eq!(positions.len(), 7);
+        assert_eq!(positions[0], Coords { x: -65, y: -1000 });
+    }
+
+    #[test]
+    fn test_insert_line() {
+        for (start, direction, steps) in [
+            (Coords { x: -130, y: -5 }, Direction::Right, 300),
+            (Coords { x: 63, y: 7 }, Direction::Right, 1),
+            (Coords { x: 64, y: 0 }, Direction::Left, 64),
+            (Coords { x: 10, y: 100 }, Direction::Left, 500),
+            (Coords { x: 0, y: 0 }, Direction::DownLeft, 100),
+            (Coords { x: 0, y: 0 }, Direction::Up, 0),
+            (Coords { x: i32::MAX - 100, y: 3 }, Direction::Right, 100),
+            (Coords { x: i32::MIN + 70, y: -1 }, Direction::Left, 70),
+        ] {
+            let mut tiled = TileSet::default();
+            tiled.insert(Coords { x: start.x + 1, y: start.y });
+            tiled.insert_line(start, direction, steps).unwrap();
+            let mut hashed = HashSet::from([Coords { x: start.x + 1, y: start.y }]);
+            hashed.insert_line(start, direction, steps).unwrap();
+
+            assert_eq!(tiled.len(), hashed.len());
+            assert_eq!(tiled.iter().collect::<HashSet<_>>(), hashed);
+        }
+
+        // A line past the edge of the coordinate range adds nothing.
+        let start = Coords { x: i32::MAX - 5, y: 0 };
+        let mut tiled = TileSet::default();
+        assert_eq!(tiled.insert_line(start, Direction::Right, 6), Err(OutOfBounds));
+        assert!(tiled.is_empty());
+        let mut hashed = HashSet::new();
+        assert_eq!(hashed.insert_line(start, Direction::UpRight, 6), Err(OutOfBounds));
+        assert!(hashed.is_empty());
+    }
+
+    fn run_both(instructions: &[Instruction], num_knots: usize) -> (HashSet<Coords>, TileSet) {
+        let tail_idx = num_knots - 1;
+        let mut hashed: Rope<Coords, HashSet<Coords>> = Rope::tracking(num_knots, &[tail_idx]);
+        hashed.run(instructions).unwrap();
+        let mut tiled: Rope<Coords, TileSet> = Rope::tracking(num_knots, &[tail_idx]);
+        tiled.run(instructions).unwrap();
+        (hashed.visited(tail_idx).unwrap().clone(), tiled.visited(tail_idx).unwrap().clone())
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        let instructions = parse_input("../inputs/d09").unwrap();
+        assert_eq!(run_both(&instructions, 2).1.len(), 6026);
+        assert_eq!(run_both(&instructions, 10).1.len(), 2273);
+
+        // Stretching the puzzle's moves makes the walk cross many tiles, in every direction.
+        for stretch in [7, 40] {
+            let walk = instructions
+                .iter()
+                .map(|instruction| Instruction { steps: instruction.steps * stretch, ..*instruction })
+                .collect::<Vec<_>>();
+            let (hashed, tiled) = run_both(&walk, 10);
+            assert_eq!(hashed.len(), tiled.len());
+            assert_eq!(tiled.iter().collect::<HashSet<_>>(), hashed);
+        }
+    }
+
+    #[test]
+    fn test_long_steps() {
+        let instructions = [Instruction { direction: Direction::Left, steps: 100_000 }];
+        let (hashed, tiled) = run_both(&instructions, 10);
+        assert_eq!(tiled.len(), 100_000 - 9 + 1);
+        assert_eq!(hashed.len(), tiled.len());
+        assert!(tiled.contains(&Coords { x: -(100_000 - 9), y: 0 }));
+    }
+}